@@ -47,6 +47,11 @@ fn main() -> ! {
     timer.set_auto_reload_preload(true);
     timer.enable_pwm_output(Channel::One, OutputCompare::Pwm1, CountDir::Up, 0.5);
 
+    // To hand individual channels to separate drivers, configure the timer, then split it.
+    // Each channel implements `embedded_hal::PwmPin`.
+    // let mut channels = timer.split();
+    // channels.c1.set_duty(channels.c1.get_max_duty() / 2);
+
     // todo: Flesh out this example.
 
     // Unmask the interrupt line.
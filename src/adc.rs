@@ -5,10 +5,12 @@ use embedded_hal::adc::{Channel, OneShot};
 
 use crate::{
     pac::{self, RCC},
-    rcc_en_reset,
     traits::ClockCfg,
 };
 
+#[cfg(not(any(feature = "f373", feature = "f3x4", feature = "f4", feature = "g0")))]
+use crate::rcc_en_reset;
+
 use cfg_if::cfg_if;
 use paste::paste;
 
@@ -57,8 +59,12 @@ pub struct Adc<ADC> {
     regs: ADC,
     ckmode: ClockMode,
     operation_mode: OperationMode,
+    resolution: Resolution,
+    align: Align,
     // Most families use u8 values for calibration, but H7 uses u16.
+    #[cfg(not(feature = "f4"))]
     cal_single_ended: Option<u16>, // Stored calibration value for single-ended
+    #[cfg(not(feature = "f4"))]
     cal_differential: Option<u16>, // Stored calibration value for differential
 }
 
@@ -116,6 +122,556 @@ pub enum OperationMode {
     Continuous = 1,
 }
 
+// Only on variants where we implement an ADC pair that shares a common block.
+#[cfg(any(
+    feature = "f302",
+    feature = "f303",
+    feature = "l4x5",
+    feature = "l4x6",
+    feature = "g4",
+    feature = "h7"
+))]
+#[derive(Clone, Copy)]
+#[repr(u8)]
+/// Multi-ADC mode, for ADC pairs that share a common block, eg ADC1 and ADC2. In these modes,
+/// the master ADC (ADC1, or ADC3 on F303 and G4) triggers the slave's conversions. (ADC_CCR DUAL)
+pub enum MultiMode {
+    /// Each ADC converts on its own.
+    Independent = 0b00000,
+    /// Combined regular simultaneous and injected simultaneous modes.
+    RegularSimultaneousInjectedSimultaneous = 0b00001,
+    /// Combined regular simultaneous and alternate trigger modes.
+    RegularSimultaneousAlternateTrigger = 0b00010,
+    /// Combined interleaved and injected simultaneous modes.
+    InterleavedInjectedSimultaneous = 0b00011,
+    /// Both ADCs convert their injected sequences at the same time.
+    InjectedSimultaneous = 0b00101,
+    /// Both ADCs convert their regular sequences at the same time.
+    RegularSimultaneous = 0b00110,
+    /// The ADCs take turns converting the same channel, offset by `set_multi_delay`, for up to
+    /// twice the sample rate.
+    Interleaved = 0b00111,
+    /// Injected triggers alternate between the master and slave.
+    AlternateTrigger = 0b01001,
+}
+
+#[derive(Clone, Copy)]
+#[repr(u8)]
+/// What happens when a conversion completes before the previous result is read. (ADC_CFGR OVRMOD)
+pub enum OverrunMode {
+    /// Keep the old result, and discard the new one. Use this when every conversion must be
+    /// read, eg with DMA.
+    Preserve = 0,
+    /// Overwrite the old result. Use this when only the latest conversion matters, eg when
+    /// polling a continuously-converting channel.
+    Overwrite = 1,
+}
+
+/// ADC error
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AdcError {
+    /// A conversion completed before the previous result was read, and was discarded.
+    Overrun,
+}
+
+#[derive(Clone, Copy)]
+#[repr(u8)]
+/// ADC interrupts. Values are the bit positions in the `ADC_IER` and `ADC_ISR` registers.
+pub enum AdcInterrupt {
+    /// ADC ready (ADRDY)
+    Ready = 0,
+    /// End of sampling phase (EOSMP)
+    EndOfSampling = 1,
+    /// End of regular conversion (EOC)
+    EndOfConversion = 2,
+    /// End of regular sequence (EOS)
+    EndOfSequence = 3,
+    /// Overrun (OVR)
+    Overrun = 4,
+    /// End of injected conversion (JEOC)
+    InjectedEndOfConversion = 5,
+    /// End of injected sequence (JEOS)
+    InjectedEndOfSequence = 6,
+    /// Analog watchdog 1 (AWD1)
+    AnalogWatchdog1 = 7,
+    /// Analog watchdog 2 (AWD2)
+    AnalogWatchdog2 = 8,
+    /// Analog watchdog 3 (AWD3)
+    AnalogWatchdog3 = 9,
+    /// Injected context queue overflow (JQOVF)
+    InjectedQueueOverflow = 10,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+/// Selects one of the 3 analog watchdogs. AWD1 monitors a single channel, or all channels.
+/// AWD2 and AWD3 can monitor any set of channels. On families other than H7, AWD2 and AWD3
+/// only compare the 8 most-significant bits of each conversion.
+pub enum AnalogWatchdog {
+    Awd1,
+    Awd2,
+    Awd3,
+}
+
+impl AnalogWatchdog {
+    /// The interrupt (and status flag) associated with this watchdog.
+    pub fn interrupt(&self) -> AdcInterrupt {
+        match self {
+            Self::Awd1 => AdcInterrupt::AnalogWatchdog1,
+            Self::Awd2 => AdcInterrupt::AnalogWatchdog2,
+            Self::Awd3 => AdcInterrupt::AnalogWatchdog3,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+/// Channels an analog watchdog monitors.
+pub enum WatchdogChannels {
+    /// Monitor all channels, regular and injected.
+    All,
+    /// Monitor a single channel.
+    Single(u8),
+    /// Monitor a set of channels, as a bit mask, eg `(1 << 3) | (1 << 5)` for channels 3 and 5.
+    /// Only valid for AWD2 and AWD3.
+    Multiple(u32),
+}
+
+// Full-scale value of a conversion at the default resolution, used to convert watchdog
+// thresholds from volts. (12-bit on most families; 16-bit on H7)
+#[cfg(not(any(feature = "h7", feature = "f373", feature = "g0")))]
+const FULL_SCALE: f32 = 4_095.;
+#[cfg(feature = "h7")]
+const FULL_SCALE: f32 = 65_535.;
+
+#[cfg(not(any(feature = "f373", feature = "g0")))]
+#[derive(Clone, Copy, PartialEq)]
+/// Internal channels, connected to on-chip sensors instead of pins. Enable them with
+/// `enable_internal_channel`. On F4, these are on ADC1; on devices where the temperature sensor
+/// and VBAT share a channel, only VBAT is converted while both are enabled. On F3, only ADC1 has
+/// the temperature sensor and VBAT channels.
+/// On L4 and L5, these are on ADC1 (and ADC3 for temperature and VBAT). On G4, they're on ADC1.
+/// On H7, they're on ADC3 (ADC2 on H7B3).
+pub enum InternalChannel {
+    /// The internal voltage reference; use to measure VDDA.
+    Vrefint,
+    /// The temperature sensor.
+    Temperature,
+    /// The backup domain supply, through an internal divider.
+    Vbat,
+}
+
+#[cfg(not(any(feature = "f373", feature = "g0")))]
+impl InternalChannel {
+    /// The ADC channel this is connected to.
+    pub fn channel(&self) -> u8 {
+        cfg_if! {
+            if #[cfg(any(feature = "f405", feature = "f407"))] {
+                match self {
+                    Self::Temperature => 16,
+                    Self::Vrefint => 17,
+                    Self::Vbat => 18,
+                }
+            } else if #[cfg(feature = "f4")] {
+                match self {
+                    Self::Vrefint => 17,
+                    Self::Temperature | Self::Vbat => 18,
+                }
+            } else if #[cfg(any(feature = "l4", feature = "l5"))] {
+                match self {
+                    Self::Vrefint => 0,
+                    Self::Temperature => 17,
+                    Self::Vbat => 18,
+                }
+            } else if #[cfg(feature = "h7")] {
+                match self {
+                    Self::Vbat => 17,
+                    Self::Temperature => 18,
+                    Self::Vrefint => 19,
+                }
+            } else {
+                match self {
+                    Self::Temperature => 16,
+                    Self::Vbat => 17,
+                    Self::Vrefint => 18,
+                }
+            }
+        }
+    }
+
+    /// The enable bit position in `ADC_CCR`. (VREFEN, TSEN/CH17SEL/VSENSEEN, VBATEN/CH18SEL)
+    fn ccr_bit(&self) -> u8 {
+        cfg_if! {
+            if #[cfg(feature = "f4")] {
+                // TSVREFE enables both the temperature sensor and VREFINT.
+                match self {
+                    Self::Vrefint | Self::Temperature => 23,
+                    Self::Vbat => 22,
+                }
+            } else {
+                match self {
+                    Self::Vrefint => 22,
+                    Self::Temperature => 23,
+                    Self::Vbat => 24,
+                }
+            }
+        }
+    }
+}
+
+// Factory calibration values, and the conditions they were measured at. These are in system
+// memory; see the datasheet's "Temperature sensor calibration values" and "Internal voltage
+// reference calibration values" tables.
+cfg_if! {
+    if #[cfg(feature = "f4")] {
+        const VREFINT_CAL: *const u16 = 0x1FFF_7A2A as *const u16;
+        const TS_CAL1: *const u16 = 0x1FFF_7A2C as *const u16;
+        const TS_CAL2: *const u16 = 0x1FFF_7A2E as *const u16;
+        const TS_CAL2_TEMP: f32 = 110.;
+        const CAL_VDDA: f32 = 3.3;
+        #[cfg(any(feature = "f405", feature = "f407"))]
+        const VBAT_DIVIDER: f32 = 2.;
+        #[cfg(not(any(feature = "f405", feature = "f407")))]
+        const VBAT_DIVIDER: f32 = 4.;
+    } else if #[cfg(all(feature = "f3", not(feature = "f373")))] {
+        const VREFINT_CAL: *const u16 = 0x1FFF_F7BA as *const u16;
+        const TS_CAL1: *const u16 = 0x1FFF_F7B8 as *const u16;
+        const TS_CAL2: *const u16 = 0x1FFF_F7C2 as *const u16;
+        const TS_CAL2_TEMP: f32 = 110.;
+        const CAL_VDDA: f32 = 3.3;
+        const VBAT_DIVIDER: f32 = 2.;
+    } else if #[cfg(any(feature = "l4", feature = "g4"))] {
+        const VREFINT_CAL: *const u16 = 0x1FFF_75AA as *const u16;
+        const TS_CAL1: *const u16 = 0x1FFF_75A8 as *const u16;
+        const TS_CAL2: *const u16 = 0x1FFF_75CA as *const u16;
+        #[cfg(any(feature = "l4x5", feature = "l4x6"))]
+        const TS_CAL2_TEMP: f32 = 110.;
+        #[cfg(not(any(feature = "l4x5", feature = "l4x6")))]
+        const TS_CAL2_TEMP: f32 = 130.;
+        const CAL_VDDA: f32 = 3.0;
+        const VBAT_DIVIDER: f32 = 3.;
+    } else if #[cfg(feature = "l5")] {
+        const VREFINT_CAL: *const u16 = 0x0BFA_05AA as *const u16;
+        const TS_CAL1: *const u16 = 0x0BFA_05A8 as *const u16;
+        const TS_CAL2: *const u16 = 0x0BFA_05CA as *const u16;
+        const TS_CAL2_TEMP: f32 = 130.;
+        const CAL_VDDA: f32 = 3.0;
+        const VBAT_DIVIDER: f32 = 3.;
+    } else if #[cfg(feature = "h7b3")] {
+        const VREFINT_CAL: *const u16 = 0x08FF_F810 as *const u16;
+        const TS_CAL1: *const u16 = 0x08FF_F814 as *const u16;
+        const TS_CAL2: *const u16 = 0x08FF_F818 as *const u16;
+        const TS_CAL2_TEMP: f32 = 110.;
+        const CAL_VDDA: f32 = 3.3;
+        const VBAT_DIVIDER: f32 = 4.;
+    } else if #[cfg(feature = "h7")] {
+        const VREFINT_CAL: *const u16 = 0x1FF1_E860 as *const u16;
+        const TS_CAL1: *const u16 = 0x1FF1_E820 as *const u16;
+        const TS_CAL2: *const u16 = 0x1FF1_E840 as *const u16;
+        const TS_CAL2_TEMP: f32 = 110.;
+        const CAL_VDDA: f32 = 3.3;
+        const VBAT_DIVIDER: f32 = 4.;
+    }
+}
+
+#[cfg(not(any(feature = "f373", feature = "g0")))]
+const TS_CAL1_TEMP: f32 = 30.;
+
+#[cfg(any(feature = "l4", feature = "l5", feature = "g4", feature = "h7"))]
+#[derive(Clone, Copy)]
+/// Number of conversions the hardware oversampler accumulates into each result.
+pub enum OversamplingRatio {
+    X2,
+    X4,
+    X8,
+    X16,
+    X32,
+    X64,
+    X128,
+    X256,
+    #[cfg(feature = "h7")]
+    X512,
+    #[cfg(feature = "h7")]
+    X1024,
+}
+
+#[cfg(any(feature = "l4", feature = "l5", feature = "g4", feature = "h7"))]
+impl OversamplingRatio {
+    /// The value to write to `ADC_CFGR2` `OVSR`.
+    fn bits(&self) -> u32 {
+        // The variant's index is log2(ratio) - 1.
+        let n = *self as u32;
+        cfg_if! {
+            if #[cfg(feature = "h7")] {
+                // On H7, OVSR is the ratio - 1.
+                (2 << n) - 1
+            } else {
+                n
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "l4", feature = "l5", feature = "g4", feature = "h7"))]
+#[derive(Clone, Copy)]
+/// Hardware oversampling configuration. (`ADC_CFGR2`) The oversampler sums `ratio` conversions,
+/// then right-shifts the sum by `shift` bits. Eg a 16x ratio with a 4-bit shift averages 16
+/// conversions, and a 16x ratio with no shift gives a 16-bit result from a 12-bit ADC.
+pub struct OversamplingCfg {
+    pub ratio: OversamplingRatio,
+    /// Right shift applied to the sum: 0 to 8 bits. (0 to 11 on H7)
+    pub shift: u8,
+    /// Oversample regular conversions. (ROVSE)
+    pub regular: bool,
+    /// Oversample injected conversions. (JOVSE)
+    pub injected: bool,
+    /// If true, each conversion of the oversampling burst needs its own trigger, instead of
+    /// the whole burst running from one trigger. (TROVS)
+    pub triggered: bool,
+    /// If true, a regular oversampling burst interrupted by an injected conversion resumes where
+    /// it left off, instead of restarting. (ROVSM)
+    pub resume_after_injected: bool,
+}
+
+#[cfg(any(feature = "l4", feature = "l5", feature = "g4", feature = "h7"))]
+impl Default for OversamplingCfg {
+    fn default() -> Self {
+        Self {
+            ratio: OversamplingRatio::X16,
+            shift: 4,
+            regular: true,
+            injected: false,
+            triggered: false,
+            resume_after_injected: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+#[repr(u8)]
+/// Which edge of an external trigger starts a conversion. (ADC_CFGR EXTEN, and ADC_JSQR JEXTEN)
+pub enum TriggerEdge {
+    /// Hardware trigger disabled; conversions are started by software.
+    Software = 0b00,
+    Rising = 0b01,
+    Falling = 0b10,
+    Both = 0b11,
+}
+
+// EXTSEL and JEXTSEL codes differ by family; these follow the RM tables for ADC1 and ADC2.
+cfg_if! {
+    if #[cfg(any(feature = "f3", feature = "l4", feature = "l5"))] {
+        #[derive(Clone, Copy)]
+        #[repr(u8)]
+        /// External trigger sources for regular conversions. (ADC_CFGR EXTSEL) These are the
+        /// mappings for ADC1 and ADC2; others (eg ADC3 and 4 on F3) may differ: See the RM, and
+        /// use `set_regular_trigger_raw` for those.
+        pub enum RegularTrigger {
+            Tim1Cc1 = 0,
+            Tim1Cc2 = 1,
+            Tim1Cc3 = 2,
+            Tim2Cc2 = 3,
+            Tim3Trgo = 4,
+            Tim4Cc4 = 5,
+            Exti11 = 6,
+            Tim8Trgo = 7,
+            Tim8Trgo2 = 8,
+            Tim1Trgo = 9,
+            Tim1Trgo2 = 10,
+            Tim2Trgo = 11,
+            Tim4Trgo = 12,
+            Tim6Trgo = 13,
+            Tim15Trgo = 14,
+            Tim3Cc4 = 15,
+        }
+
+        #[derive(Clone, Copy)]
+        #[repr(u8)]
+        /// External trigger sources for injected conversions. (ADC_JSQR JEXTSEL) These are the
+        /// mappings for ADC1 and ADC2; others may differ: See the RM, and use
+        /// `set_injected_trigger_raw` for those.
+        pub enum InjectedTrigger {
+            Tim1Trgo = 0,
+            Tim1Cc4 = 1,
+            Tim2Trgo = 2,
+            Tim2Cc1 = 3,
+            Tim3Cc4 = 4,
+            Tim4Trgo = 5,
+            Exti15 = 6,
+            Tim8Cc4 = 7,
+            Tim1Trgo2 = 8,
+            Tim8Trgo = 9,
+            Tim8Trgo2 = 10,
+            Tim3Cc3 = 11,
+            Tim3Trgo = 12,
+            Tim3Cc1 = 13,
+            Tim6Trgo = 14,
+            Tim15Trgo = 15,
+        }
+    } else if #[cfg(feature = "g4")] {
+        #[derive(Clone, Copy)]
+        #[repr(u8)]
+        /// External trigger sources for regular conversions. (ADC_CFGR EXTSEL) These are the
+        /// mappings for ADC1 and ADC2; ADC3, 4 and 5 differ: See RM0440, and use
+        /// `set_regular_trigger_raw` for those, and for the HRTIM triggers.
+        pub enum RegularTrigger {
+            Tim1Cc1 = 0,
+            Tim1Cc2 = 1,
+            Tim1Cc3 = 2,
+            Tim2Cc2 = 3,
+            Tim3Trgo = 4,
+            Tim4Cc4 = 5,
+            Exti11 = 6,
+            Tim8Trgo = 7,
+            Tim8Trgo2 = 8,
+            Tim1Trgo = 9,
+            Tim1Trgo2 = 10,
+            Tim2Trgo = 11,
+            Tim4Trgo = 12,
+            Tim6Trgo = 13,
+            Tim15Trgo = 14,
+            Tim3Cc4 = 15,
+            Tim20Trgo = 16,
+            Tim20Trgo2 = 17,
+            Tim20Cc1 = 18,
+            Tim20Cc2 = 19,
+            Tim20Cc3 = 20,
+            LptimOut = 29,
+            Tim7Trgo = 30,
+        }
+
+        #[derive(Clone, Copy)]
+        #[repr(u8)]
+        /// External trigger sources for injected conversions. (ADC_JSQR JEXTSEL) These are the
+        /// mappings for ADC1 and ADC2; ADC3, 4 and 5 differ: See RM0440, and use
+        /// `set_injected_trigger_raw` for those, and for the HRTIM triggers.
+        pub enum InjectedTrigger {
+            Tim1Trgo = 0,
+            Tim1Cc4 = 1,
+            Tim2Trgo = 2,
+            Tim2Cc1 = 3,
+            Tim3Cc4 = 4,
+            Tim4Trgo = 5,
+            Exti15 = 6,
+            Tim8Cc4 = 7,
+            Tim1Trgo2 = 8,
+            Tim8Trgo = 9,
+            Tim8Trgo2 = 10,
+            Tim3Cc3 = 11,
+            Tim3Trgo = 12,
+            Tim3Cc1 = 13,
+            Tim6Trgo = 14,
+            Tim15Trgo = 15,
+            Tim20Trgo = 16,
+            Tim20Trgo2 = 17,
+            Tim20Cc4 = 18,
+            LptimOut = 29,
+            Tim7Trgo = 30,
+        }
+    } else if #[cfg(feature = "h7")] {
+        #[derive(Clone, Copy)]
+        #[repr(u8)]
+        /// External trigger sources for regular conversions. (ADC_CFGR EXTSEL) ADC1, 2 and 3
+        /// share this mapping.
+        pub enum RegularTrigger {
+            Tim1Cc1 = 0,
+            Tim1Cc2 = 1,
+            Tim1Cc3 = 2,
+            Tim2Cc2 = 3,
+            Tim3Trgo = 4,
+            Tim4Cc4 = 5,
+            Exti11 = 6,
+            Tim8Trgo = 7,
+            Tim8Trgo2 = 8,
+            Tim1Trgo = 9,
+            Tim1Trgo2 = 10,
+            Tim2Trgo = 11,
+            Tim4Trgo = 12,
+            Tim6Trgo = 13,
+            Tim15Trgo = 14,
+            Tim3Cc4 = 15,
+            HrtimAdcTrg1 = 16,
+            HrtimAdcTrg3 = 17,
+            Lptim1Out = 18,
+            Lptim2Out = 19,
+            Lptim3Out = 20,
+        }
+
+        #[derive(Clone, Copy)]
+        #[repr(u8)]
+        /// External trigger sources for injected conversions. (ADC_JSQR JEXTSEL) ADC1, 2 and 3
+        /// share this mapping.
+        pub enum InjectedTrigger {
+            Tim1Trgo = 0,
+            Tim1Cc4 = 1,
+            Tim2Trgo = 2,
+            Tim2Cc1 = 3,
+            Tim3Cc4 = 4,
+            Tim4Trgo = 5,
+            Exti15 = 6,
+            Tim8Cc4 = 7,
+            Tim1Trgo2 = 8,
+            Tim8Trgo = 9,
+            Tim8Trgo2 = 10,
+            Tim3Cc3 = 11,
+            Tim3Trgo = 12,
+            Tim3Cc1 = 13,
+            Tim6Trgo = 14,
+            Tim15Trgo = 15,
+            HrtimAdcTrg2 = 16,
+            HrtimAdcTrg4 = 17,
+            Lptim1Out = 18,
+            Lptim2Out = 19,
+            Lptim3Out = 20,
+        }
+    } else if #[cfg(feature = "f4")] {
+        #[derive(Clone, Copy)]
+        #[repr(u8)]
+        /// External trigger sources for regular conversions. (ADC_CR2 EXTSEL) All ADCs share
+        /// this mapping.
+        pub enum RegularTrigger {
+            Tim1Cc1 = 0,
+            Tim1Cc2 = 1,
+            Tim1Cc3 = 2,
+            Tim2Cc2 = 3,
+            Tim2Cc3 = 4,
+            Tim2Cc4 = 5,
+            Tim2Trgo = 6,
+            Tim3Cc1 = 7,
+            Tim3Trgo = 8,
+            Tim4Cc4 = 9,
+            Tim5Cc1 = 10,
+            Tim5Cc2 = 11,
+            Tim5Cc3 = 12,
+            Tim8Cc1 = 13,
+            Tim8Trgo = 14,
+            Exti11 = 15,
+        }
+
+        #[derive(Clone, Copy)]
+        #[repr(u8)]
+        /// External trigger sources for injected conversions. (ADC_CR2 JEXTSEL) All ADCs share
+        /// this mapping.
+        pub enum InjectedTrigger {
+            Tim1Cc4 = 0,
+            Tim1Trgo = 1,
+            Tim2Cc1 = 2,
+            Tim2Trgo = 3,
+            Tim3Cc2 = 4,
+            Tim3Cc4 = 5,
+            Tim4Cc1 = 6,
+            Tim4Cc2 = 7,
+            Tim4Cc3 = 8,
+            Tim4Trgo = 9,
+            Tim5Cc4 = 10,
+            Tim5Trgo = 11,
+            Tim8Cc2 = 12,
+            Tim8Cc3 = 13,
+            Tim8Cc4 = 14,
+            Exti15 = 15,
+        }
+    }
+}
+
 // #[cfg(any(feature = "l4", feature = "l5"))]
 // #[derive(Clone, Copy, PartialEq)]
 // #[repr(u8)]
@@ -148,6 +704,13 @@ pub enum ClockMode {
     SyncDiv2 = 0b10,
     /// Use AHB clock rcc_hclk3 divided by 4
     SyncDiv4 = 0b11,
+    // F4 sets ADCPRE from these variants with a match, instead of from their discriminants.
+    /// F4 only: Use PCLK2 divided by 6
+    #[cfg(feature = "f4")]
+    SyncDiv6,
+    /// F4 only: Use PCLK2 divided by 8
+    #[cfg(feature = "f4")]
+    SyncDiv8,
 }
 
 // #[cfg(any(feature = "f3"))]
@@ -157,9 +720,86 @@ impl Default for ClockMode {
     }
 }
 
+#[derive(Clone, Copy, PartialEq)]
+/// ADC conversion resolution. (ADC_CFGR RES) Lower resolutions convert faster.
+pub enum Resolution {
+    #[cfg(feature = "h7")]
+    B16,
+    #[cfg(feature = "h7")]
+    B14,
+    B12,
+    B10,
+    B8,
+    #[cfg(not(feature = "h7"))]
+    B6,
+}
+
+impl Default for Resolution {
+    /// The reset value: 12 bits; 16 bits on H7.
+    fn default() -> Self {
+        cfg_if! {
+            if #[cfg(feature = "h7")] {
+                Resolution::B16
+            } else {
+                Resolution::B12
+            }
+        }
+    }
+}
+
+impl Resolution {
+    /// The number of bits in a conversion.
+    pub fn num_bits(&self) -> u8 {
+        match self {
+            #[cfg(feature = "h7")]
+            Self::B16 => 16,
+            #[cfg(feature = "h7")]
+            Self::B14 => 14,
+            Self::B12 => 12,
+            Self::B10 => 10,
+            Self::B8 => 8,
+            #[cfg(not(feature = "h7"))]
+            Self::B6 => 6,
+        }
+    }
+
+    /// The maximum reading, eg 4_095 for 12 bits.
+    pub fn full_scale(&self) -> u32 {
+        (1 << self.num_bits()) - 1
+    }
+
+    /// The value to write to the `RES` field.
+    #[cfg(not(any(feature = "f373", feature = "g0")))]
+    fn bits(&self) -> u8 {
+        match self {
+            #[cfg(feature = "h7")]
+            Self::B16 => 0b000,
+            #[cfg(feature = "h7")]
+            Self::B14 => 0b001,
+            #[cfg(feature = "h7")]
+            Self::B12 => 0b010,
+            #[cfg(feature = "h7")]
+            Self::B10 => 0b011,
+            // Rev Y devices use 0b100 for 8 bits; rev V devices, 0b111.
+            #[cfg(any(feature = "h743", feature = "h753"))]
+            Self::B8 => 0b100,
+            #[cfg(all(feature = "h7", not(any(feature = "h743", feature = "h753"))))]
+            Self::B8 => 0b111,
+            #[cfg(not(feature = "h7"))]
+            Self::B12 => 0b00,
+            #[cfg(not(feature = "h7"))]
+            Self::B10 => 0b01,
+            #[cfg(not(feature = "h7"))]
+            Self::B8 => 0b10,
+            #[cfg(not(feature = "h7"))]
+            Self::B6 => 0b11,
+        }
+    }
+}
+
 #[cfg(not(feature = "h7"))]
 /// ADC data register alignment
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 #[repr(u8)]
 pub enum Align {
     /// Right alignment of output data
@@ -177,7 +817,7 @@ impl Default for Align {
 
 #[cfg(feature = "h7")]
 /// ADC data register alignment
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 #[repr(u8)]
 pub enum Align {
     NoShift = 0,
@@ -205,6 +845,22 @@ impl Default for Align {
     }
 }
 
+#[cfg(feature = "h7")]
+/// Enable and reset an H7 ADC's peripheral clock.
+macro_rules! h7_rcc_en_reset {
+    (3, $rcc:expr) => {
+        $rcc.ahb4enr.modify(|_, w| w.adc3en().set_bit());
+        $rcc.ahb4rstr.modify(|_, w| w.adc3rst().set_bit());
+        $rcc.ahb4rstr.modify(|_, w| w.adc3rst().clear_bit());
+    };
+    ($num:tt, $rcc:expr) => {
+        paste! {
+            rcc_en_reset!(ahb1, [<adc $num>], $rcc);
+        }
+    };
+}
+
+#[cfg(not(any(feature = "f373", feature = "f3x4", feature = "f4", feature = "g0")))]
 // Abstract implementation of ADC functionality
 macro_rules! hal {
     ($ADC:ident, $ADC_COMMON:ident, $adc:ident, $rcc_num:tt) => {
@@ -230,6 +886,8 @@ macro_rules! hal {
                         regs,
                         ckmode,
                         operation_mode: OperationMode::OneShot,
+                        resolution: Resolution::default(),
+                        align: Align::default(),
                         cal_single_ended: None,
                         cal_differential: None,
                     };
@@ -253,7 +911,7 @@ macro_rules! hal {
                     asm::delay(adc_per_cpu_cycles * 4);
                     this_adc.enable();
 
-                    this_adc.setup_oneshot(); // todo: Setup Continuous
+                    this_adc.setup_oneshot();
 
                     this_adc
                 }
@@ -266,13 +924,11 @@ macro_rules! hal {
                  // todo: Macros instead of AdcNum? More consistent with other modules. Not sure which is more apt.
                 paste! {
                     cfg_if! {
-                        if #[cfg(any(feature = "f3", feature = "h7"))] {
-                            rcc_en_reset!(ahb1, [<adc $rcc_num>], rcc);
-                        } else if #[cfg(feature = "f4")] {
-                            rcc_en_reset!(2, [<adc $rcc_num>], rcc);
-                        } else if #[cfg(any(feature = "h7"))] {
-                        // todo: 1 and 2 are on ahb1enr etc. 3 is on ahb4. 3 won't work here.
+                        if #[cfg(feature = "f3")] {
                             rcc_en_reset!(ahb1, [<adc $rcc_num>], rcc);
+                        } else if #[cfg(feature = "h7")] {
+                            // ADC1 and 2 are on AHB1; ADC3 is on AHB4.
+                            h7_rcc_en_reset!($rcc_num, rcc);
                         } else if #[cfg(any(feature = "g4"))] {
                             rcc_en_reset!(ahb2, [<adc $rcc_num>], rcc);
                         } else {  // ie L4, L5 etc.
@@ -293,8 +949,8 @@ macro_rules! hal {
 
             /// sets up adc in one shot mode for a single channel
             pub fn setup_oneshot(&mut self) {
-                self.regs.cr.modify(|_, w| w.adstp().set_bit());
-                self.regs.isr.modify(|_, w| w.ovr().clear_bit());
+                self.stop_conversions();
+                self.clear_interrupt(AdcInterrupt::Overrun);
 
                 self.regs.cfgr.modify(|_, w| w
                     .cont().clear_bit()  // single conversion mode.
@@ -306,6 +962,29 @@ macro_rules! hal {
                 self.operation_mode = OperationMode::OneShot;
             }
 
+            /// Set up continuous conversions of a single channel: Each conversion starts as soon as
+            /// the previous one completes. Old results are overwritten, so `read_result` always
+            /// returns the latest; use `set_overrun_mode` to change this. Start conversions with
+            /// `start_conversion` or `start_conversion_nb`.
+            pub fn setup_continuous(&mut self) {
+                self.stop_conversions();
+                self.clear_interrupt(AdcInterrupt::Overrun);
+
+                self.regs.cfgr.modify(|_, w| w.cont().set_bit());
+                self.set_overrun_mode(OverrunMode::Overwrite);
+
+                self.set_sequence_len(1);
+
+                self.operation_mode = OperationMode::Continuous;
+            }
+
+            /// Set whether a new result overwrites an unread one. Stops any ongoing conversions.
+            pub fn set_overrun_mode(&mut self, mode: OverrunMode) {
+                // RM: Only allowed when ADSTART = 0.
+                self.stop_conversions();
+                self.regs.cfgr.modify(|_, w| w.ovrmod().bit(mode as u8 != 0));
+            }
+
             pub fn set_sequence_len(&mut self, len: u8) {
                 if len - 1 >= 16 {
                     panic!("ADC sequence length must be in 1..=16")
@@ -321,12 +1000,88 @@ macro_rules! hal {
                 }
             }
 
-            pub fn set_align(&self, align: Align) {
+            pub fn set_align(&mut self, align: Align) {
                 #[cfg(feature = "h7")]
                 self.regs.cfgr2.modify(|_, w| w.lshift().bits(align as u8));
 
                 #[cfg(not(feature = "h7"))]
                 self.regs.cfgr.modify(|_, w| w.align().bit(align as u8 != 0));
+
+                self.align = align;
+            }
+
+            /// Set the conversion resolution. Stops any ongoing conversions.
+            pub fn set_resolution(&mut self, resolution: Resolution) {
+                // RM: Only allowed when ADSTART = 0 and JADSTART = 0.
+                self.stop_conversions();
+
+                cfg_if! {
+                    if #[cfg(feature = "h7")] {
+                        // RES: bits 2:4
+                        let (mask, val) = (0b111 << 2, (resolution.bits() as u32) << 2);
+                    } else {
+                        // RES: bits 3:4
+                        let (mask, val) = (0b11 << 3, (resolution.bits() as u32) << 3);
+                    }
+                }
+                self.regs.cfgr.modify(|r, w| unsafe { w.bits((r.bits() & !mask) | val) });
+
+                self.resolution = resolution;
+            }
+
+            /// Remove the alignment from a reading, ie right-align it, so it's in the range
+            /// `0..=resolution.full_scale()`.
+            fn right_aligned(&self, reading: u32) -> u32 {
+                cfg_if! {
+                    if #[cfg(feature = "h7")] {
+                        reading >> (self.align as u8)
+                    } else {
+                        if self.align == Align::Left {
+                            // Left-aligned data fills a 16-bit register, except for 6-bit
+                            // conversions, which fill a byte.
+                            if self.resolution == Resolution::B6 {
+                                reading >> 2
+                            } else {
+                                reading >> (16 - self.resolution.num_bits())
+                            }
+                        } else {
+                            reading
+                        }
+                    }
+                }
+            }
+
+            /// Convert a single-ended reading to millivolts, accounting for resolution and
+            /// alignment. `vdda_mv` is the reference voltage; measure it with `read_vdda` for
+            /// accurate results.
+            pub fn reading_to_mv(&self, reading: u32, vdda_mv: u32) -> u32 {
+                self.right_aligned(reading) * vdda_mv / self.resolution.full_scale()
+            }
+
+            /// Convert a differential reading, which is offset-binary, to a signed value, centered
+            /// on 0: Positive when the positive input is higher.
+            pub fn reading_to_signed(&self, reading: u32) -> i32 {
+                self.right_aligned(reading) as i32 - (1 << (self.resolution.num_bits() - 1))
+            }
+
+            /// Convert a differential reading to the voltage between its inputs, in millivolts.
+            pub fn reading_to_differential_mv(&self, reading: u32, vdda_mv: u32) -> i32 {
+                // The full differential range is -VDDA to +VDDA.
+                self.reading_to_signed(reading) * vdda_mv as i32
+                    / (1 << (self.resolution.num_bits() - 1))
+            }
+
+            /// Take a single reading, in OneShot mode, and convert it to millivolts.
+            pub fn read_mv(&mut self, channel: u8, vdda_mv: u32) -> u32 {
+                let reading = self.read(channel) as u32;
+                self.reading_to_mv(reading, vdda_mv)
+            }
+
+            /// Take a single reading of a differential channel, in OneShot mode, as a signed value.
+            /// Set the channel to differential with `set_input_type` first.
+            pub fn read_differential(&mut self, channel: u8) -> i32 {
+                let reading = self.read(channel) as u32;
+                self.reading_to_signed(reading)
             }
 
             /// Enable the ADC.
@@ -562,9 +1317,10 @@ macro_rules! hal {
                     self.disable();
                 }
 
-                // todo: Figure out how you do the bit shift math here; we don't have individual fields.
-                // difsel!(self.regs, channel, input_type);
-                // self.regs.difsel.write(|w| w.difsel_1_15r().bits(input_type as u8));
+                // DIFSEL has one bit per channel.
+                self.regs.difsel.modify(|r, w| unsafe {
+                    w.bits((r.bits() & !(1 << channel)) | ((input_type as u32) << channel))
+                });
 
                 if was_enabled {
                     self.enable();
@@ -573,11 +1329,39 @@ macro_rules! hal {
 
             /// Start a conversion: Either a single measurement, or continuous conversions.
             /// See L4 RM 16.4.15 for details.
+            /// Blocks until the conversion is complete in OneShot mode; returns immediately in
+            /// Continuous mode.
             pub fn start_conversion(&mut self, chan: u8, mode: OperationMode) {
+                self.start_conversion_nb(chan, mode);
+
+                if let OperationMode::OneShot = mode {
+                    // After the regular sequence is complete, after each conversion is complete,
+                    // the EOC (end of regular conversion) flag is set.
+                    // After the regular sequence is complete: The EOS (end of regular sequence) flag is set.
+                    // (We're ignoring eoc, since this module doesn't currently support sequences)
+                    while self.regs.isr.read().eos().bit_is_clear() {}  // wait until complete.
+                }
+            }
+
+            /// Start a conversion without blocking. Check for completion with `is_ready`, poll with
+            /// `read_nb`, or use the `EndOfConversion` interrupt, then read with `read_result`.
+            pub fn start_conversion_nb(&mut self, chan: u8, mode: OperationMode) {
+                // RM: CONT can only be changed when ADSTART = 0.
+                self.stop_conversions();
+
                 // Set continuous or differential mode.
                 self.regs.cfgr.modify(|_, w| w.cont().bit(mode as u8 != 0));
+                self.operation_mode = mode;
                 self.select_channel(chan);
 
+                // Clear flags from previous conversions, so they don't read as this one's.
+                // (EOC, EOS, OVR; write 1 to clear)
+                self.regs.isr.write(|w| unsafe {
+                    w.bits((1 << AdcInterrupt::EndOfConversion as u8)
+                        | (1 << AdcInterrupt::EndOfSequence as u8)
+                        | (1 << AdcInterrupt::Overrun as u8))
+                });
+
                 // L4 RM: In Single conversion mode, the ADC performs once all the conversions of the channels.
                 // This mode is started with the CONT bit at 0 by either:
                 // • Setting the ADSTART bit in the ADC_CR register (for a regular channel)
@@ -585,24 +1369,46 @@ macro_rules! hal {
                 // • External hardware trigger event (for a regular or injected channel)
                 // (Here, we assume a regular channel)
                 self.regs.cr.modify(|_, w| w.adstart().set_bit());  // Start
+            }
+
+            /// Check if a conversion result is ready to read. (EOC) Reading the result clears this.
+            pub fn is_ready(&self) -> bool {
+                self.regs.isr.read().eoc().bit_is_set()
+            }
+
+            /// Check if a result was lost, since a conversion completed before the previous result
+            /// was read. (OVR) Clear with `clear_interrupt(AdcInterrupt::Overrun)`.
+            pub fn is_overrun(&self) -> bool {
+                self.regs.isr.read().ovr().bit_is_set()
+            }
+
+            /// Read a conversion result if one is ready, without blocking. Returns an `Overrun`
+            /// error, and clears the flag, if a result was discarded in `OverrunMode::Preserve`.
+            pub fn read_nb(&mut self) -> nb::Result<u32, AdcError> {
+                if self.is_overrun() && self.regs.cfgr.read().ovrmod().bit_is_clear() {
+                    self.clear_interrupt(AdcInterrupt::Overrun);
+                    return Err(nb::Error::Other(AdcError::Overrun));
+                }
+
+                if !self.is_ready() {
+                    return Err(nb::Error::WouldBlock);
+                }
 
-                // After the regular sequence is complete, after each conversion is complete,
-                // the EOC (end of regular conversion) flag is set.
-                // After the regular sequence is complete: The EOS (end of regular sequence) flag is set.
-                // (We're ignoring eoc, since this module doesn't currently support sequences)
-                while self.regs.isr.read().eos().bit_is_clear() {}  // wait until complete.
+                Ok(self.read_result())
             }
 
             /// Read data from a conversion. In OneShot mode, this will generally be run right
-            /// after `start_conversion`.
-            pub fn read_result(&mut self) -> u16 {
-                self.regs.dr.read().bits() as u16
+            /// after `start_conversion`. This is wider than `u16` for oversampled results on H7,
+            /// which can be up to 26 bits.
+            pub fn read_result(&mut self) -> u32 {
+                self.regs.dr.read().bits()
             }
 
             /// Select the channel to sample. Note that this register allows setting a sequence,
             /// but for now, we only support converting one channel at a time.
-            /// (Single-ended or differential, but not a sequence.)
-            fn select_channel(&self, chan: u8) {
+            /// (Single-ended or differential, but not a sequence.) In multi-ADC modes, use this to
+            /// select the slave's channel; the master's is selected when starting a conversion.
+            pub fn select_channel(&self, chan: u8) {
                 // Channel as u8 is the ADC channel to use.
                 self.regs.sqr1.modify(|_, w| unsafe { w.sq1().bits(chan) });
             }
@@ -610,109 +1416,597 @@ macro_rules! hal {
             /// Select the sample time for a given channel.
             pub fn set_sample_time(&mut self, chan: u8, smp: SampleTime) {
                 // Channel is the ADC channel to use.
+                if chan > 19 {
+                    panic!("ADC channel must be in 0..=19");
+                }
 
                 // RM: Note: only allowed when ADSTART = 0 and JADSTART = 0.
                 self.stop_conversions();
+
+                // SMPx fields are 3 bits each: Channels 0-9 in SMPR1, and 10-19 in SMPR2.
+                let smp = smp as u32;
                 unsafe {
-                    match chan {
-                        1 => self.regs.smpr1.modify(|_, w| w.smp1().bits(smp as u8)),
-                        2 => self.regs.smpr1.modify(|_, w| w.smp2().bits(smp as u8)),
-                        3 => self.regs.smpr1.modify(|_, w| w.smp3().bits(smp as u8)),
-                        4 => self.regs.smpr1.modify(|_, w| w.smp4().bits(smp as u8)),
-                        5 => self.regs.smpr1.modify(|_, w| w.smp5().bits(smp as u8)),
-                        6 => self.regs.smpr1.modify(|_, w| w.smp6().bits(smp as u8)),
-                        7 => self.regs.smpr1.modify(|_, w| w.smp7().bits(smp as u8)),
-                        8 => self.regs.smpr1.modify(|_, w| w.smp8().bits(smp as u8)),
-                        9 => self.regs.smpr1.modify(|_, w| w.smp9().bits(smp as u8)),
-                        11 => self.regs.smpr2.modify(|_, w| w.smp10().bits(smp as u8)),
-                        12 => self.regs.smpr2.modify(|_, w| w.smp12().bits(smp as u8)),
-                        13 => self.regs.smpr2.modify(|_, w| w.smp13().bits(smp as u8)),
-                        14 => self.regs.smpr2.modify(|_, w| w.smp14().bits(smp as u8)),
-                        15 => self.regs.smpr2.modify(|_, w| w.smp15().bits(smp as u8)),
-                        16 => self.regs.smpr2.modify(|_, w| w.smp16().bits(smp as u8)),
-                        17 => self.regs.smpr2.modify(|_, w| w.smp17().bits(smp as u8)),
-                        18 => self.regs.smpr2.modify(|_, w| w.smp18().bits(smp as u8)),
-                        // 19 => self.regs.smpr2.modify(|_, w| w.smp19().bits(smp as u8)),
-                        // 20 => self.regs.smpr2.modify(|_, w| w.smp20().bits(smp as u8)),
-                        _ => unreachable!(),
-                    };
+                    if chan < 10 {
+                        let shift = chan * 3;
+                        self.regs.smpr1.modify(|r, w| {
+                            w.bits((r.bits() & !(0b111 << shift)) | (smp << shift))
+                        });
+                    } else {
+                        let shift = (chan - 10) * 3;
+                        self.regs.smpr2.modify(|r, w| {
+                            w.bits((r.bits() & !(0b111 << shift)) | (smp << shift))
+                        });
+                    }
                 }
             }
 
-            /// Take a single reading, in OneShot mode
+            /// Take a single reading, in OneShot mode. Use `read_result` directly for oversampled
+            /// results wider than 16 bits.
             pub fn read(&mut self, channel: u8) -> u16 {
                 self.start_conversion(channel, OperationMode::OneShot);
-                self.read_result()
+                self.read_result() as u16
             }
-        }
 
-        impl<WORD, PIN> OneShot<pac::$ADC, WORD, PIN> for Adc<pac::$ADC>
-        where
-            WORD: From<u16>,
-            PIN: Channel<pac::$ADC, ID = u8>,
-            {
-                type Error = ();
+            /// Enable an internal channel, by setting its bit in the common `ADC_CCR` register. The
+            /// temperature sensor needs some time to start: See the datasheet's `t_START`.
+            pub fn enable_internal_channel(
+                &mut self,
+                common_regs: &mut pac::$ADC_COMMON,
+                channel: InternalChannel,
+            ) {
+                common_regs.ccr.modify(|r, w| unsafe {
+                    w.bits(r.bits() | (1 << channel.ccr_bit()))
+                });
+
+                // On H7, channels must be preselected before converting them.
+                #[cfg(feature = "h7")]
+                self.regs.pcsel.modify(|r, w| unsafe {
+                    w.bits(r.bits() | (1 << channel.channel()))
+                });
+            }
 
-                fn read(&mut self, _pin: &mut PIN) -> nb::Result<WORD, Self::Error> {
-                    Ok(Self::read(self, PIN::channel()).into())
+            /// Disable an internal channel. VBAT in particular drains the battery while enabled.
+            pub fn disable_internal_channel(
+                &mut self,
+                common_regs: &mut pac::$ADC_COMMON,
+                channel: InternalChannel,
+            ) {
+                common_regs.ccr.modify(|r, w| unsafe {
+                    w.bits(r.bits() & !(1 << channel.ccr_bit()))
+                });
+            }
+
+            /// Take a reading of an internal channel, using the longest sample time, as the
+            /// datasheet's minimum sample times for these are long. The channel's previous sample
+            /// time is restored afterwards. The result is scaled to the default resolution, which
+            /// the calibration values use.
+            fn read_internal(&mut self, channel: InternalChannel) -> f32 {
+                let chan = channel.channel();
+                // Save the sample times, so we can restore the user's configuration afterwards.
+                let smpr1 = self.regs.smpr1.read().bits();
+                let smpr2 = self.regs.smpr2.read().bits();
+
+                self.set_sample_time(chan, SampleTime::T601);
+                let reading = self.read(chan) as u32;
+                let reading = self.right_aligned(reading) as f32;
+
+                self.regs.smpr1.write(|w| unsafe { w.bits(smpr1) });
+                self.regs.smpr2.write(|w| unsafe { w.bits(smpr2) });
+
+                reading * FULL_SCALE / self.resolution.full_scale() as f32
+            }
+
+            /// Measure VDDA, in volts, by reading VREFINT, and comparing it to its factory
+            /// calibration value. Use this to convert readings to volts accurately.
+            /// `InternalChannel::Vrefint` must be enabled.
+            pub fn read_vdda(&mut self) -> f32 {
+                let vrefint_cal = unsafe { core::ptr::read_volatile(VREFINT_CAL) } as f32;
+                CAL_VDDA * vrefint_cal / self.read_internal(InternalChannel::Vrefint)
+            }
+
+            /// Measure the temperature, in °C, using the factory calibration values. `vdda` is
+            /// in volts, eg from `read_vdda`. `InternalChannel::Temperature` must be enabled.
+            pub fn read_temperature(&mut self, vdda: f32) -> f32 {
+                let ts_cal1 = unsafe { core::ptr::read_volatile(TS_CAL1) } as f32;
+                let ts_cal2 = unsafe { core::ptr::read_volatile(TS_CAL2) } as f32;
+
+                // The calibration values were measured at `CAL_VDDA`; scale our reading to match.
+                let reading = self.read_internal(InternalChannel::Temperature) * vdda / CAL_VDDA;
+
+                (TS_CAL2_TEMP - TS_CAL1_TEMP) / (ts_cal2 - ts_cal1) * (reading - ts_cal1)
+                    + TS_CAL1_TEMP
+            }
+
+            /// Measure VBAT, in volts, accounting for the internal divider. `vdda` is in volts, eg
+            /// from `read_vdda`. `InternalChannel::Vbat` must be enabled.
+            pub fn read_vbat(&mut self, vdda: f32) -> f32 {
+                self.read_internal(InternalChannel::Vbat) / FULL_SCALE * vdda * VBAT_DIVIDER
+            }
+
+            /// Set the injected sequence: Up to 4 channels, converted in order when the injected
+            /// group is triggered, preempting any regular conversion in progress. Stops any
+            /// ongoing conversions. See the RM section "Injected channel management".
+            pub fn set_injected_sequence(&mut self, channels: &[u8]) {
+                if channels.is_empty() || channels.len() > 4 {
+                    panic!("ADC injected sequence length must be in 1..=4")
                 }
-        }
 
-        // todo: This mess is due to how EH implements the OneShot trait.
-        impl Channel<pac::$ADC> for AdcChannel::C1 {
-            type ID = u8;
-            fn channel() -> u8 { 1 }
-        }
-        impl Channel<pac::$ADC> for AdcChannel::C2 {
-            type ID = u8;
-            fn channel() -> u8 { 2 }
-        }
-        impl Channel<pac::$ADC> for AdcChannel::C3 {
-            type ID = u8;
-            fn channel() -> u8 { 3}
-        }
-        impl Channel<pac::$ADC> for AdcChannel::C4 {
-            type ID = u8;
-            fn channel() -> u8 { 4 }
-        }
-        impl Channel<pac::$ADC> for AdcChannel::C5 {
-            type ID = u8;
-            fn channel() -> u8 { 5 }
-        }
-        impl Channel<pac::$ADC> for AdcChannel::C6 {
-            type ID = u8;
-            fn channel() -> u8 { 6 }
-        }
-        impl Channel<pac::$ADC> for AdcChannel::C7 {
-            type ID = u8;
-            fn channel() -> u8 { 7 }
-        }
-        impl Channel<pac::$ADC> for AdcChannel::C8 {
-            type ID = u8;
-            fn channel() -> u8 { 8 }
-        }
-        impl Channel<pac::$ADC> for AdcChannel::C9 {
-            type ID = u8;
-            fn channel() -> u8 { 9 }
-        }
-        impl Channel<pac::$ADC> for AdcChannel::C10 {
-            type ID = u8;
-            fn channel() -> u8 { 10 }
-        }
-        impl Channel<pac::$ADC> for AdcChannel::C11 {
-            type ID = u8;
-            fn channel() -> u8 { 11 }
-        }
-        impl Channel<pac::$ADC> for AdcChannel::C12 {
-            type ID = u8;
-            fn channel() -> u8 { 12 }
-        }
-        impl Channel<pac::$ADC> for AdcChannel::C13 {
-            type ID = u8;
-            fn channel() -> u8 { 13 }
-        }
-        impl Channel<pac::$ADC> for AdcChannel::C14 {
-            type ID = u8;
+                // A JSQR write while injected conversions are running only takes effect at the next
+                // injected trigger (or is queued, on families with a context queue). We stop
+                // conversions so the new sequence is used immediately.
+                self.stop_conversions();
+
+                let chan = |i: usize| *channels.get(i).unwrap_or(&0);
+
+                self.regs.jsqr.modify(|_, w| unsafe {
+                    w.jl().bits(channels.len() as u8 - 1);
+                    w.jsq1().bits(chan(0));
+                    w.jsq2().bits(chan(1));
+                    w.jsq3().bits(chan(2));
+                    w.jsq4().bits(chan(3))
+                });
+            }
+
+            /// Start converting the injected sequence, by setting `JADSTART`. If no hardware trigger is
+            /// set for the injected group, conversion starts immediately. Otherwise, this arms the
+            /// trigger, and conversions start on each trigger event. Doesn't block.
+            pub fn start_injected_conversion(&mut self) {
+                self.regs.cr.modify(|_, w| w.jadstart().set_bit());
+            }
+
+            /// Check if the injected sequence has completed. (JEOS)
+            pub fn injected_sequence_complete(&self) -> bool {
+                self.regs.isr.read().jeos().bit_is_set()
+            }
+
+            /// Read the result of an injected conversion; `rank` is 1 - 4, in sequence order.
+            /// If an offset is set for the channel, the result is signed; use `as i16`.
+            pub fn read_injected(&self, rank: u8) -> u16 {
+                match rank {
+                    1 => self.regs.jdr1.read().bits() as u16,
+                    2 => self.regs.jdr2.read().bits() as u16,
+                    3 => self.regs.jdr3.read().bits() as u16,
+                    4 => self.regs.jdr4.read().bits() as u16,
+                    _ => panic!("ADC injected rank must be in 1..=4"),
+                }
+            }
+
+            /// Convert the injected sequence, block until it's complete, and write the results
+            /// into `results`, in sequence order.
+            pub fn read_injected_sequence(&mut self, results: &mut [u16]) {
+                self.clear_interrupt(AdcInterrupt::InjectedEndOfSequence);
+                self.start_injected_conversion();
+                while !self.injected_sequence_complete() {}
+                self.clear_interrupt(AdcInterrupt::InjectedEndOfSequence);
+
+                for (i, result) in results.iter_mut().take(4).enumerate() {
+                    *result = self.read_injected(i as u8 + 1);
+                }
+            }
+
+            /// Set an offset, which is subtracted from every conversion of a channel, regular or
+            /// injected. `index` selects one of the 4 offset registers (`ADC_OFRy`), and `offset` is
+            /// in raw counts. Results may be negative. Stops any ongoing conversions.
+            pub fn set_offset(&mut self, index: u8, channel: u8, offset: u32) {
+                // RM: Only allowed when ADSTART = 0 and JADSTART = 0.
+                self.stop_conversions();
+
+                cfg_if! {
+                    if #[cfg(feature = "h7")] {
+                        // H7 has no enable bit; an offset of 0 has no effect.
+                        let val = ((channel as u32 & 0x1f) << 26) | (offset & 0x3ff_ffff);
+                    } else {
+                        // OFFSETy_EN | OFFSETy_CH | OFFSETy
+                        let val = (1 << 31) | ((channel as u32 & 0x1f) << 26) | (offset & 0xfff);
+                    }
+                }
+
+                unsafe {
+                    match index {
+                        1 => self.regs.ofr1.write(|w| w.bits(val)),
+                        2 => self.regs.ofr2.write(|w| w.bits(val)),
+                        3 => self.regs.ofr3.write(|w| w.bits(val)),
+                        4 => self.regs.ofr4.write(|w| w.bits(val)),
+                        _ => panic!("ADC offset index must be in 1..=4"),
+                    }
+                }
+            }
+
+            /// Disable an offset set with `set_offset`.
+            pub fn disable_offset(&mut self, index: u8) {
+                self.stop_conversions();
+
+                unsafe {
+                    match index {
+                        1 => self.regs.ofr1.write(|w| w.bits(0)),
+                        2 => self.regs.ofr2.write(|w| w.bits(0)),
+                        3 => self.regs.ofr3.write(|w| w.bits(0)),
+                        4 => self.regs.ofr4.write(|w| w.bits(0)),
+                        _ => panic!("ADC offset index must be in 1..=4"),
+                    }
+                }
+            }
+
+            /// Select a hardware trigger to start regular conversions, eg a timer's TRGO, at exact
+            /// intervals. Then, call `start_triggered_conversion` to arm it. Use `TriggerEdge::Software`
+            /// to go back to starting conversions from software. Stops any ongoing conversions.
+            pub fn set_regular_trigger(&mut self, trigger: RegularTrigger, edge: TriggerEdge) {
+                self.set_regular_trigger_raw(trigger as u8, edge);
+            }
+
+            /// Like `set_regular_trigger`, but takes a raw `EXTSEL` value, for ADCs whose trigger
+            /// mapping differs from `RegularTrigger`.
+            pub fn set_regular_trigger_raw(&mut self, extsel: u8, edge: TriggerEdge) {
+                // RM: Only allowed when ADSTART = 0.
+                self.stop_conversions();
+
+                self.regs.cfgr.modify(|_, w| unsafe {
+                    w.exten().bits(edge as u8);
+                    w.extsel().bits(extsel)
+                });
+            }
+
+            /// Select a hardware trigger to start injected conversions, eg a timer's capture-compare
+            /// event, mid-PWM-period. Then, call `start_injected_conversion` to arm it. Stops any
+            /// ongoing conversions.
+            pub fn set_injected_trigger(&mut self, trigger: InjectedTrigger, edge: TriggerEdge) {
+                self.set_injected_trigger_raw(trigger as u8, edge);
+            }
+
+            /// Like `set_injected_trigger`, but takes a raw `JEXTSEL` value, for ADCs whose trigger
+            /// mapping differs from `InjectedTrigger`.
+            pub fn set_injected_trigger_raw(&mut self, jextsel: u8, edge: TriggerEdge) {
+                self.stop_conversions();
+
+                self.regs.jsqr.modify(|_, w| unsafe {
+                    w.jexten().bits(edge as u8);
+                    w.jextsel().bits(jextsel)
+                });
+            }
+
+            /// Arm regular conversions, by setting `ADSTART`, without blocking. If a hardware trigger
+            /// is set, conversions of the regular sequence start on each trigger event; read results
+            /// with `read_result`, eg in the end-of-conversion interrupt.
+            pub fn start_triggered_conversion(&mut self) {
+                self.regs.cr.modify(|_, w| w.adstart().set_bit());
+            }
+
+            #[cfg(any(feature = "l4", feature = "l5", feature = "g4", feature = "h7"))]
+            /// Enable the hardware oversampler, for higher effective resolution, or lower noise,
+            /// without CPU averaging. Stops any ongoing conversions.
+            pub fn enable_oversampling(&mut self, cfg: &OversamplingCfg) {
+                // RM: Only allowed when ADSTART = 0 and JADSTART = 0.
+                self.stop_conversions();
+
+                cfg_if! {
+                    if #[cfg(feature = "h7")] {
+                        if cfg.shift > 11 {
+                            panic!("ADC oversampling shift must be in 0..=11");
+                        }
+                        // OVSR: bits 16:25
+                        let ratio = cfg.ratio.bits() << 16;
+                        let mask = (0x3ff << 16) | 0x7e3;
+                    } else {
+                        if cfg.shift > 8 {
+                            panic!("ADC oversampling shift must be in 0..=8");
+                        }
+                        // OVSR: bits 2:4
+                        let ratio = cfg.ratio.bits() << 2;
+                        let mask = 0x7ff;
+                    }
+                }
+
+                // ROVSE: bit 0, JOVSE: bit 1, OVSS: bits 5:8, TROVS: bit 9, ROVSM: bit 10
+                let val = ratio
+                    | (cfg.regular as u32)
+                    | ((cfg.injected as u32) << 1)
+                    | ((cfg.shift as u32) << 5)
+                    | ((cfg.triggered as u32) << 9)
+                    | ((cfg.resume_after_injected as u32) << 10);
+
+                self.regs.cfgr2.modify(|r, w| unsafe { w.bits((r.bits() & !mask) | val) });
+            }
+
+            #[cfg(any(feature = "l4", feature = "l5", feature = "g4", feature = "h7"))]
+            /// Disable the hardware oversampler, for regular and injected conversions.
+            pub fn disable_oversampling(&mut self) {
+                self.stop_conversions();
+
+                // ROVSE, JOVSE
+                self.regs.cfgr2.modify(|r, w| unsafe { w.bits(r.bits() & !0b11) });
+            }
+
+            /// Select the channels an analog watchdog monitors, and enable it. Set thresholds with
+            /// `set_watchdog_thresholds`, and enable its interrupt with `enable_interrupt` to be
+            /// notified when a conversion falls outside them. AWD1 monitors both regular and injected
+            /// conversions. Stops any ongoing conversions.
+            pub fn enable_watchdog(&mut self, watchdog: AnalogWatchdog, channels: WatchdogChannels) {
+                // RM: Only allowed when ADSTART = 0 and JADSTART = 0.
+                self.stop_conversions();
+
+                match watchdog {
+                    AnalogWatchdog::Awd1 => {
+                        // AWD1SGL: bit 22, AWD1EN: bit 23, JAWD1EN: bit 24, AWD1CH: bits 26:30
+                        let val = match channels {
+                            WatchdogChannels::All => 0b11 << 23,
+                            WatchdogChannels::Single(ch) => {
+                                (0b111 << 22) | ((ch as u32 & 0x1f) << 26)
+                            }
+                            WatchdogChannels::Multiple(_) => {
+                                panic!("AWD1 can only monitor a single channel, or all channels.")
+                            }
+                        };
+                        self.regs.cfgr.modify(|r, w| unsafe {
+                            w.bits((r.bits() & !(0b1_1111_0111 << 22)) | val)
+                        });
+                    }
+                    _ => {
+                        let val = match channels {
+                            WatchdogChannels::All => 0xf_ffff,
+                            WatchdogChannels::Single(ch) => 1 << ch,
+                            WatchdogChannels::Multiple(mask) => mask,
+                        };
+                        self.write_awd_cr(watchdog, val);
+                    }
+                }
+            }
+
+            /// Disable an analog watchdog.
+            pub fn disable_watchdog(&mut self, watchdog: AnalogWatchdog) {
+                self.stop_conversions();
+
+                match watchdog {
+                    AnalogWatchdog::Awd1 => {
+                        self.regs.cfgr.modify(|r, w| unsafe { w.bits(r.bits() & !(0b11 << 23)) });
+                    }
+                    _ => self.write_awd_cr(watchdog, 0),
+                }
+            }
+
+            /// Write the channel mask of AWD2 or AWD3. (`ADC_AWD2CR`, `ADC_AWD3CR`)
+            fn write_awd_cr(&mut self, watchdog: AnalogWatchdog, mask: u32) {
+                cfg_if! {
+                    if #[cfg(feature = "h7")] {
+                        let mask = mask & 0xf_ffff;
+                    } else {
+                        let mask = mask & 0x7_ffff;
+                    }
+                }
+
+                unsafe {
+                    match watchdog {
+                        AnalogWatchdog::Awd2 => self.regs.awd2cr.write(|w| w.bits(mask)),
+                        AnalogWatchdog::Awd3 => self.regs.awd3cr.write(|w| w.bits(mask)),
+                        AnalogWatchdog::Awd1 => unreachable!(),
+                    }
+                }
+            }
+
+            /// Set an analog watchdog's thresholds, in raw counts at the default resolution (12-bit;
+            /// 16-bit on H7). The watchdog flags conversions below `low`, or above `high`. On
+            /// families other than H7, AWD2 and AWD3 ignore the 4 least-significant bits.
+            /// Stops any ongoing conversions.
+            pub fn set_watchdog_thresholds(&mut self, watchdog: AnalogWatchdog, low: u32, high: u32) {
+                self.stop_conversions();
+
+                cfg_if! {
+                    if #[cfg(feature = "h7")] {
+                        let low = low & 0x3ff_ffff;
+                        let high = high & 0x3ff_ffff;
+
+                        unsafe {
+                            match watchdog {
+                                AnalogWatchdog::Awd1 => {
+                                    self.regs.ltr1.write(|w| w.bits(low));
+                                    self.regs.htr1.write(|w| w.bits(high));
+                                }
+                                AnalogWatchdog::Awd2 => {
+                                    self.regs.ltr2.write(|w| w.bits(low));
+                                    self.regs.htr2.write(|w| w.bits(high));
+                                }
+                                AnalogWatchdog::Awd3 => {
+                                    self.regs.ltr3.write(|w| w.bits(low));
+                                    self.regs.htr3.write(|w| w.bits(high));
+                                }
+                            }
+                        }
+                    } else {
+                        match watchdog {
+                            AnalogWatchdog::Awd1 => {
+                                // HT1: bits 16:27, LT1: bits 0:11. Leave bits 12:15 (G4 AWDFILT) as-is.
+                                let val = ((high & 0xfff) << 16) | (low & 0xfff);
+                                self.regs.tr1.modify(|r, w| unsafe {
+                                    w.bits((r.bits() & 0xf000) | val)
+                                });
+                            }
+                            _ => {
+                                // HTx: bits 16:23, LTx: bits 0:7; compared against the 8 MSBs.
+                                let val = (((high >> 4) & 0xff) << 16) | ((low >> 4) & 0xff);
+                                unsafe {
+                                    match watchdog {
+                                        AnalogWatchdog::Awd2 => self.regs.tr2.write(|w| w.bits(val)),
+                                        _ => self.regs.tr3.write(|w| w.bits(val)),
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            /// Set an analog watchdog's thresholds, in volts. `vdda` is the ADC reference voltage.
+            pub fn set_watchdog_thresholds_volts(
+                &mut self,
+                watchdog: AnalogWatchdog,
+                low: f32,
+                high: f32,
+                vdda: f32,
+            ) {
+                let to_counts = |v: f32| {
+                    let counts = v / vdda * FULL_SCALE;
+                    if counts <= 0. {
+                        0
+                    } else if counts >= FULL_SCALE {
+                        FULL_SCALE as u32
+                    } else {
+                        counts as u32
+                    }
+                };
+
+                self.set_watchdog_thresholds(watchdog, to_counts(low), to_counts(high));
+            }
+
+            /// Check if an analog watchdog has flagged a conversion outside its thresholds. Clear
+            /// the flag with `clear_interrupt(watchdog.interrupt())`.
+            pub fn watchdog_triggered(&self, watchdog: AnalogWatchdog) -> bool {
+                self.regs.isr.read().bits() & (1 << watchdog.interrupt() as u8) != 0
+            }
+
+            /// Enable an interrupt.
+            pub fn enable_interrupt(&mut self, interrupt: AdcInterrupt) {
+                self.regs.ier.modify(|r, w| unsafe { w.bits(r.bits() | (1 << interrupt as u8)) });
+            }
+
+            /// Disable an interrupt.
+            pub fn disable_interrupt(&mut self, interrupt: AdcInterrupt) {
+                self.regs.ier.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << interrupt as u8)) });
+            }
+
+            /// Clear an interrupt flag. Run this in the interrupt handler, so it doesn't
+            /// fire repeatedly.
+            pub fn clear_interrupt(&mut self, interrupt: AdcInterrupt) {
+                // These flags are cleared by writing 1; we write, instead of modifying, to avoid
+                // clearing other flags.
+                self.regs.isr.write(|w| unsafe { w.bits(1 << interrupt as u8) });
+            }
+        }
+
+        oneshot!($ADC);
+    }
+}
+
+// Multi-ADC mode methods, for the master ADC of a pair that shares a common block.
+#[cfg(any(
+    feature = "f302",
+    feature = "f303",
+    feature = "l4x5",
+    feature = "l4x6",
+    feature = "g4",
+    feature = "h7"
+))]
+macro_rules! multi_mode {
+    ($ADC:ident, $ADC_COMMON:ident) => {
+        impl Adc<pac::$ADC> {
+            /// Set the multi-ADC mode, for this ADC (the master) and the other in its pair. Then,
+            /// configure the slave's channels and sample times, and start conversions on the
+            /// master only; read both results with `read_dual`. Stops any ongoing conversions on
+            /// this ADC; stop the slave's too before calling this.
+            pub fn set_multi_mode(&mut self, common_regs: &mut pac::$ADC_COMMON, mode: MultiMode) {
+                // RM: Only allowed when ADSTART = 0 and JADSTART = 0, on both the master and slave.
+                self.stop_conversions();
+
+                // DUAL: bits 0:4
+                common_regs.ccr.modify(|r, w| unsafe {
+                    w.bits((r.bits() & !0b1_1111) | mode as u32)
+                });
+            }
+
+            /// Set the delay between the master and slave sampling phases in interleaved mode, in
+            /// ADC clock cycles, minus 1. See the RM for the minimum for each resolution.
+            pub fn set_multi_delay(&mut self, common_regs: &mut pac::$ADC_COMMON, delay: u8) {
+                if delay > 0xf {
+                    panic!("ADC multi-mode delay must be in 0..=15");
+                }
+                self.stop_conversions();
+
+                // DELAY: bits 8:11
+                common_regs.ccr.modify(|r, w| unsafe {
+                    w.bits((r.bits() & !(0xf << 8)) | ((delay as u32) << 8))
+                });
+            }
+
+            /// Read the latest regular results of both ADCs in a pair, from the common data register
+            /// (`ADC_CDR`), in one access. Returns `(master, slave)`.
+            pub fn read_dual(&self, common_regs: &pac::$ADC_COMMON) -> (u16, u16) {
+                // RDATA_MST: bits 0:15, RDATA_SLV: bits 16:31
+                let cdr = common_regs.cdr.read().bits();
+                (cdr as u16, (cdr >> 16) as u16)
+            }
+        }
+    };
+}
+
+#[cfg(not(any(feature = "f373", feature = "f3x4", feature = "g0")))]
+/// Implement the Embedded HAL `OneShot` trait, and its channels.
+macro_rules! oneshot {
+    ($ADC:ident) => {
+        impl<WORD, PIN> OneShot<pac::$ADC, WORD, PIN> for Adc<pac::$ADC>
+        where
+            WORD: From<u16>,
+            PIN: Channel<pac::$ADC, ID = u8>,
+            {
+                type Error = ();
+
+                fn read(&mut self, _pin: &mut PIN) -> nb::Result<WORD, Self::Error> {
+                    Ok(Self::read(self, PIN::channel()).into())
+                }
+        }
+
+        // todo: This mess is due to how EH implements the OneShot trait.
+        impl Channel<pac::$ADC> for AdcChannel::C1 {
+            type ID = u8;
+            fn channel() -> u8 { 1 }
+        }
+        impl Channel<pac::$ADC> for AdcChannel::C2 {
+            type ID = u8;
+            fn channel() -> u8 { 2 }
+        }
+        impl Channel<pac::$ADC> for AdcChannel::C3 {
+            type ID = u8;
+            fn channel() -> u8 { 3}
+        }
+        impl Channel<pac::$ADC> for AdcChannel::C4 {
+            type ID = u8;
+            fn channel() -> u8 { 4 }
+        }
+        impl Channel<pac::$ADC> for AdcChannel::C5 {
+            type ID = u8;
+            fn channel() -> u8 { 5 }
+        }
+        impl Channel<pac::$ADC> for AdcChannel::C6 {
+            type ID = u8;
+            fn channel() -> u8 { 6 }
+        }
+        impl Channel<pac::$ADC> for AdcChannel::C7 {
+            type ID = u8;
+            fn channel() -> u8 { 7 }
+        }
+        impl Channel<pac::$ADC> for AdcChannel::C8 {
+            type ID = u8;
+            fn channel() -> u8 { 8 }
+        }
+        impl Channel<pac::$ADC> for AdcChannel::C9 {
+            type ID = u8;
+            fn channel() -> u8 { 9 }
+        }
+        impl Channel<pac::$ADC> for AdcChannel::C10 {
+            type ID = u8;
+            fn channel() -> u8 { 10 }
+        }
+        impl Channel<pac::$ADC> for AdcChannel::C11 {
+            type ID = u8;
+            fn channel() -> u8 { 11 }
+        }
+        impl Channel<pac::$ADC> for AdcChannel::C12 {
+            type ID = u8;
+            fn channel() -> u8 { 12 }
+        }
+        impl Channel<pac::$ADC> for AdcChannel::C13 {
+            type ID = u8;
+            fn channel() -> u8 { 13 }
+        }
+        impl Channel<pac::$ADC> for AdcChannel::C14 {
+            type ID = u8;
             fn channel() -> u8 { 14 }
         }
         impl Channel<pac::$ADC> for AdcChannel::C15 {
@@ -742,6 +2036,520 @@ macro_rules! hal {
     }
 }
 
+#[cfg(feature = "f4")]
+/// F4 uses an older ADC peripheral, with a different register layout. This implements the same
+/// API as the other families, for the features it shares with them. F4 lacks:
+/// - `set_overrun_mode`: New results always overwrite old ones.
+/// - `calibrate`, `set_input_type`, and the differential-reading methods: There's no
+///   calibration, and no differential inputs.
+/// - `enable_oversampling`: There's no hardware oversampling; average readings in software.
+/// - `set_offset`: Offsets only apply to injected conversions, by rank; use
+///   `set_injected_offset`.
+/// - `start_triggered_conversion`: Hardware triggers don't need arming.
+/// - `AnalogWatchdog::Awd2` and `Awd3`: There's a single analog watchdog.
+/// - `set_multi_mode`, `set_multi_delay`, and `read_dual`: Not implemented yet.
+macro_rules! hal_f4 {
+    ($ADC:ident, $adc:ident) => {
+        impl Adc<pac::$ADC> {
+            paste! {
+                /// Init a new ADC
+                ///
+                /// Enables the clock, and enables the ADC. `ckmode` sets the prescaler from PCLK2,
+                /// which is shared by all ADCs: `SyncDiv2`, `SyncDiv4`, `SyncDiv6`, or `SyncDiv8`.
+                pub fn [<new_ $adc>]<C: ClockCfg>(
+                    regs: pac::$ADC,
+                    adc_common : &mut pac::ADC_COMMON,
+                    ckmode: ClockMode,
+                    clocks: &C,
+                    rcc: &mut RCC,
+                ) -> Self {
+                    let mut this_adc = Self {
+                        regs,
+                        ckmode,
+                        operation_mode: OperationMode::OneShot,
+                        resolution: Resolution::default(),
+                        align: Align::default(),
+                    };
+
+                    // The ADCs share a single reset bit, so we don't reset here, to avoid resetting
+                    // ADCs already set up.
+                    rcc.apb2enr.modify(|_, w| w.[<$adc en>]().set_bit());
+
+                    let adcpre = match this_adc.ckmode {
+                        ClockMode::SyncDiv2 => 0b00,
+                        ClockMode::SyncDiv4 => 0b01,
+                        ClockMode::SyncDiv6 => 0b10,
+                        ClockMode::SyncDiv8 => 0b11,
+                        ClockMode::SyncDiv1 => panic!("The ADC clock on F4 must be divided by 2 or more."),
+                    };
+                    adc_common.ccr.modify(|_, w| w.adcpre().bits(adcpre));
+
+                    this_adc.set_align(Align::default());
+                    this_adc.enable(clocks);
+                    this_adc.setup_oneshot();
+
+                    this_adc
+                }
+            }
+
+            /// Enable the ADC, and wait for it to stabilize.
+            fn enable<C: ClockCfg>(&mut self, clocks: &C) {
+                self.regs.cr2.modify(|_, w| w.adon().set_bit());
+                // DS: t_STAB is 3µs max.
+                asm::delay(clocks.sysclk() / 1_000_000 * 3);
+            }
+
+            /// Disable the ADC, eg to save power.
+            pub fn disable(&mut self) {
+                self.regs.cr2.modify(|_, w| w.adon().clear_bit());
+            }
+
+            /// sets up adc in one shot mode for a single channel
+            pub fn setup_oneshot(&mut self) {
+                self.stop_conversions();
+                self.regs.cr2.modify(|_, w| w.cont().clear_bit());
+                self.set_sequence_len(1);
+
+                self.operation_mode = OperationMode::OneShot;
+            }
+
+            /// Set up continuous conversions of a single channel: Each conversion starts as soon as
+            /// the previous one completes. On F4, old results are always overwritten, so
+            /// `read_result` returns the latest. Start conversions with `start_conversion` or
+            /// `start_conversion_nb`.
+            pub fn setup_continuous(&mut self) {
+                self.stop_conversions();
+                self.regs.cr2.modify(|_, w| w.cont().set_bit());
+                self.set_sequence_len(1);
+
+                self.operation_mode = OperationMode::Continuous;
+            }
+
+            /// Set the number of conversions in the regular sequence, 1 - 16.
+            pub fn set_sequence_len(&mut self, len: u8) {
+                if len == 0 || len > 16 {
+                    panic!("ADC sequence length must be in 1..=16")
+                }
+
+                self.regs.sqr1.modify(|_, w| w.l().bits(len - 1));
+            }
+
+            /// Set the data register alignment.
+            pub fn set_align(&mut self, align: Align) {
+                self.regs.cr2.modify(|_, w| w.align().bit(align as u8 != 0));
+                self.align = align;
+            }
+
+            /// Set the conversion resolution. Stops any ongoing conversions.
+            pub fn set_resolution(&mut self, resolution: Resolution) {
+                self.stop_conversions();
+                self.regs.cr1.modify(|_, w| w.res().bits(resolution.bits()));
+                self.resolution = resolution;
+            }
+
+            /// Remove the alignment from a reading, ie right-align it, so it's in the range
+            /// `0..=resolution.full_scale()`.
+            fn right_aligned(&self, reading: u32) -> u32 {
+                if self.align == Align::Left {
+                    // Left-aligned data fills a 16-bit register, except for 6-bit
+                    // conversions, which fill a byte.
+                    if self.resolution == Resolution::B6 {
+                        reading >> 2
+                    } else {
+                        reading >> (16 - self.resolution.num_bits())
+                    }
+                } else {
+                    reading
+                }
+            }
+
+            /// Convert a reading to millivolts, accounting for resolution and alignment. `vdda_mv`
+            /// is the reference voltage; measure it with `read_vdda` for accurate results.
+            pub fn reading_to_mv(&self, reading: u32, vdda_mv: u32) -> u32 {
+                self.right_aligned(reading) * vdda_mv / self.resolution.full_scale()
+            }
+
+            /// Take a single reading, in OneShot mode, and convert it to millivolts.
+            pub fn read_mv(&mut self, channel: u8, vdda_mv: u32) -> u32 {
+                let reading = self.read(channel) as u32;
+                self.reading_to_mv(reading, vdda_mv)
+            }
+
+            /// Stop continuous conversions. F4 can't abort a conversion in progress, but each
+            /// takes at most a few µs.
+            pub fn stop_conversions(&mut self) {
+                self.regs.cr2.modify(|_, w| w.cont().clear_bit());
+            }
+
+            /// Select the sample time for a given channel.
+            pub fn set_sample_time(&mut self, chan: u8, smp: SampleTime) {
+                if chan > 18 {
+                    panic!("ADC channel must be in 0..=18");
+                }
+
+                // SMPx fields are 3 bits each: Channels 0-9 in SMPR2, and 10-18 in SMPR1.
+                let smp = smp as u32;
+                unsafe {
+                    if chan < 10 {
+                        let shift = chan * 3;
+                        self.regs.smpr2.modify(|r, w| {
+                            w.bits((r.bits() & !(0b111 << shift)) | (smp << shift))
+                        });
+                    } else {
+                        let shift = (chan - 10) * 3;
+                        self.regs.smpr1.modify(|r, w| {
+                            w.bits((r.bits() & !(0b111 << shift)) | (smp << shift))
+                        });
+                    }
+                }
+            }
+
+            /// Start a conversion: Either a single measurement, or continuous conversions.
+            /// Blocks until the conversion is complete in OneShot mode; returns immediately in
+            /// Continuous mode.
+            pub fn start_conversion(&mut self, chan: u8, mode: OperationMode) {
+                self.start_conversion_nb(chan, mode);
+
+                if let OperationMode::OneShot = mode {
+                    while !self.is_ready() {}
+                }
+            }
+
+            /// Start a conversion without blocking. Check for completion with `is_ready`, poll with
+            /// `read_nb`, or use the `EndOfConversion` interrupt, then read with `read_result`.
+            pub fn start_conversion_nb(&mut self, chan: u8, mode: OperationMode) {
+                self.regs.cr2.modify(|_, w| w.cont().bit(mode as u8 != 0));
+                self.operation_mode = mode;
+                self.select_channel(chan);
+
+                // Clear flags from previous conversions. (EOC, STRT, OVR; write 0 to clear)
+                self.regs.sr.write(|w| unsafe { w.bits(!((1 << 1) | (1 << 4) | (1 << 5))) });
+
+                self.regs.cr2.modify(|_, w| w.swstart().set_bit());
+            }
+
+            /// Check if a conversion result is ready to read. (EOC) Reading the result clears this.
+            pub fn is_ready(&self) -> bool {
+                self.regs.sr.read().eoc().bit_is_set()
+            }
+
+            /// Check if a result was lost. (OVR) On F4, this only happens when using DMA.
+            pub fn is_overrun(&self) -> bool {
+                self.regs.sr.read().ovr().bit_is_set()
+            }
+
+            /// Read a conversion result if one is ready, without blocking.
+            pub fn read_nb(&mut self) -> nb::Result<u32, AdcError> {
+                if self.is_overrun() {
+                    self.clear_interrupt(AdcInterrupt::Overrun);
+                    return Err(nb::Error::Other(AdcError::Overrun));
+                }
+
+                if !self.is_ready() {
+                    return Err(nb::Error::WouldBlock);
+                }
+
+                Ok(self.read_result())
+            }
+
+            /// Read data from a conversion. In OneShot mode, this will generally be run right
+            /// after `start_conversion`.
+            pub fn read_result(&mut self) -> u32 {
+                self.regs.dr.read().bits()
+            }
+
+            /// Select the channel to sample.
+            pub fn select_channel(&self, chan: u8) {
+                self.regs.sqr3.modify(|_, w| unsafe { w.sq1().bits(chan) });
+            }
+
+            /// Take a single reading, in OneShot mode
+            pub fn read(&mut self, channel: u8) -> u16 {
+                self.start_conversion(channel, OperationMode::OneShot);
+                self.read_result() as u16
+            }
+
+            /// Enable an internal channel, by setting its bit in the common `ADC_CCR` register.
+            /// On F4, VREFINT and the temperature sensor are enabled together.
+            pub fn enable_internal_channel(
+                &mut self,
+                common_regs: &mut pac::ADC_COMMON,
+                channel: InternalChannel,
+            ) {
+                common_regs.ccr.modify(|r, w| unsafe {
+                    w.bits(r.bits() | (1 << channel.ccr_bit()))
+                });
+            }
+
+            /// Disable an internal channel. VBAT in particular drains the battery while enabled.
+            pub fn disable_internal_channel(
+                &mut self,
+                common_regs: &mut pac::ADC_COMMON,
+                channel: InternalChannel,
+            ) {
+                common_regs.ccr.modify(|r, w| unsafe {
+                    w.bits(r.bits() & !(1 << channel.ccr_bit()))
+                });
+            }
+
+            /// Take a reading of an internal channel, using the longest sample time, as the
+            /// datasheet's minimum sample times for these are long. The channel's previous sample
+            /// time is restored afterwards. The result is scaled to 12 bits, which the calibration
+            /// values use.
+            fn read_internal(&mut self, channel: InternalChannel) -> f32 {
+                let chan = channel.channel();
+                // Save the sample times, so we can restore the user's configuration afterwards.
+                let smpr1 = self.regs.smpr1.read().bits();
+                let smpr2 = self.regs.smpr2.read().bits();
+
+                self.set_sample_time(chan, SampleTime::T601);
+                let reading = self.read(chan) as u32;
+                let reading = self.right_aligned(reading) as f32;
+
+                self.regs.smpr1.write(|w| unsafe { w.bits(smpr1) });
+                self.regs.smpr2.write(|w| unsafe { w.bits(smpr2) });
+
+                reading * FULL_SCALE / self.resolution.full_scale() as f32
+            }
+
+            /// Measure VDDA, in volts, by reading VREFINT, and comparing it to its factory
+            /// calibration value. `InternalChannel::Vrefint` must be enabled.
+            pub fn read_vdda(&mut self) -> f32 {
+                let vrefint_cal = unsafe { core::ptr::read_volatile(VREFINT_CAL) } as f32;
+                CAL_VDDA * vrefint_cal / self.read_internal(InternalChannel::Vrefint)
+            }
+
+            /// Measure the temperature, in °C, using the factory calibration values. `vdda` is
+            /// in volts, eg from `read_vdda`. `InternalChannel::Temperature` must be enabled.
+            pub fn read_temperature(&mut self, vdda: f32) -> f32 {
+                let ts_cal1 = unsafe { core::ptr::read_volatile(TS_CAL1) } as f32;
+                let ts_cal2 = unsafe { core::ptr::read_volatile(TS_CAL2) } as f32;
+
+                let reading = self.read_internal(InternalChannel::Temperature) * vdda / CAL_VDDA;
+
+                (TS_CAL2_TEMP - TS_CAL1_TEMP) / (ts_cal2 - ts_cal1) * (reading - ts_cal1)
+                    + TS_CAL1_TEMP
+            }
+
+            /// Measure VBAT, in volts, accounting for the internal divider. `vdda` is in volts, eg
+            /// from `read_vdda`. `InternalChannel::Vbat` must be enabled.
+            pub fn read_vbat(&mut self, vdda: f32) -> f32 {
+                self.read_internal(InternalChannel::Vbat) / FULL_SCALE * vdda * VBAT_DIVIDER
+            }
+
+            /// Set the injected sequence: Up to 4 channels, converted in order. Stops any ongoing
+            /// conversions.
+            pub fn set_injected_sequence(&mut self, channels: &[u8]) {
+                if channels.is_empty() || channels.len() > 4 {
+                    panic!("ADC injected sequence length must be in 1..=4")
+                }
+
+                self.stop_conversions();
+
+                // RM: "If JL[1:0] length is less than 4, the channels are converted in a sequence
+                // starting from (4-JL)", ie the sequence ends at JSQ4.
+                let start = 4 - channels.len();
+                let chan = |i: usize| if i < start { 0 } else { channels[i - start] };
+
+                self.regs.jsqr.modify(|_, w| unsafe {
+                    w.jl().bits(channels.len() as u8 - 1);
+                    w.jsq1().bits(chan(0));
+                    w.jsq2().bits(chan(1));
+                    w.jsq3().bits(chan(2));
+                    w.jsq4().bits(chan(3))
+                });
+
+                // Scan mode converts every channel in the sequence, instead of only the first.
+                self.regs.cr1.modify(|_, w| w.scan().bit(channels.len() > 1));
+            }
+
+            /// Start converting the injected sequence, by setting `JSWSTART`. Doesn't block. If a
+            /// hardware trigger is set for the injected group, there's no need to call this:
+            /// Conversions start on each trigger event.
+            pub fn start_injected_conversion(&mut self) {
+                self.regs.cr2.modify(|_, w| w.jswstart().set_bit());
+            }
+
+            /// Check if the injected sequence has completed. (JEOC; on F4, this is set at the end
+            /// of the sequence)
+            pub fn injected_sequence_complete(&self) -> bool {
+                self.regs.sr.read().jeoc().bit_is_set()
+            }
+
+            /// Read the result of an injected conversion; `rank` is 1 - 4, in sequence order.
+            /// If an offset is set for the rank, the result is signed; use `as i16`.
+            pub fn read_injected(&self, rank: u8) -> u16 {
+                match rank {
+                    1 => self.regs.jdr1.read().bits() as u16,
+                    2 => self.regs.jdr2.read().bits() as u16,
+                    3 => self.regs.jdr3.read().bits() as u16,
+                    4 => self.regs.jdr4.read().bits() as u16,
+                    _ => panic!("ADC injected rank must be in 1..=4"),
+                }
+            }
+
+            /// Convert the injected sequence, block until it's complete, and write the results
+            /// into `results`, in sequence order.
+            pub fn read_injected_sequence(&mut self, results: &mut [u16]) {
+                self.clear_interrupt(AdcInterrupt::InjectedEndOfSequence);
+                self.start_injected_conversion();
+                while !self.injected_sequence_complete() {}
+                self.clear_interrupt(AdcInterrupt::InjectedEndOfSequence);
+
+                for (i, result) in results.iter_mut().take(4).enumerate() {
+                    *result = self.read_injected(i as u8 + 1);
+                }
+            }
+
+            /// Set an offset, which is subtracted from injected conversions of a rank, 1 - 4.
+            /// (`ADC_JOFRx`) Unlike on other families, F4 has no offsets for regular conversions,
+            /// and they're selected by rank, not channel. `offset` is in raw counts; set it to 0
+            /// to disable it.
+            pub fn set_injected_offset(&mut self, rank: u8, offset: u16) {
+                let offset = offset as u32 & 0xfff;
+
+                unsafe {
+                    match rank {
+                        1 => self.regs.jofr1.write(|w| w.bits(offset)),
+                        2 => self.regs.jofr2.write(|w| w.bits(offset)),
+                        3 => self.regs.jofr3.write(|w| w.bits(offset)),
+                        4 => self.regs.jofr4.write(|w| w.bits(offset)),
+                        _ => panic!("ADC injected rank must be in 1..=4"),
+                    }
+                }
+            }
+
+            /// Select a hardware trigger to start regular conversions, eg a timer's TRGO, at exact
+            /// intervals. Unlike on other families, there's no need to arm it: Conversions start
+            /// on each trigger event. Use `TriggerEdge::Software` to go back to starting
+            /// conversions from software.
+            pub fn set_regular_trigger(&mut self, trigger: RegularTrigger, edge: TriggerEdge) {
+                self.set_regular_trigger_raw(trigger as u8, edge);
+            }
+
+            /// Like `set_regular_trigger`, but takes a raw `EXTSEL` value.
+            pub fn set_regular_trigger_raw(&mut self, extsel: u8, edge: TriggerEdge) {
+                self.regs.cr2.modify(|_, w| unsafe {
+                    w.exten().bits(edge as u8);
+                    w.extsel().bits(extsel)
+                });
+            }
+
+            /// Select a hardware trigger to start injected conversions, eg a timer's capture-compare
+            /// event, mid-PWM-period. Conversions start on each trigger event.
+            pub fn set_injected_trigger(&mut self, trigger: InjectedTrigger, edge: TriggerEdge) {
+                self.set_injected_trigger_raw(trigger as u8, edge);
+            }
+
+            /// Like `set_injected_trigger`, but takes a raw `JEXTSEL` value.
+            pub fn set_injected_trigger_raw(&mut self, jextsel: u8, edge: TriggerEdge) {
+                self.regs.cr2.modify(|_, w| unsafe {
+                    w.jexten().bits(edge as u8);
+                    w.jextsel().bits(jextsel)
+                });
+            }
+
+            /// Enable the analog watchdog. F4 only has `AnalogWatchdog::Awd1`, which monitors a
+            /// single channel, or all channels.
+            pub fn enable_watchdog(&mut self, watchdog: AnalogWatchdog, channels: WatchdogChannels) {
+                if watchdog != AnalogWatchdog::Awd1 {
+                    panic!("F4 only has analog watchdog 1.")
+                }
+
+                self.regs.cr1.modify(|_, w| unsafe {
+                    match channels {
+                        WatchdogChannels::All => w.awdsgl().clear_bit(),
+                        WatchdogChannels::Single(ch) => w.awdsgl().set_bit().awdch().bits(ch),
+                        WatchdogChannels::Multiple(_) => {
+                            panic!("AWD1 can only monitor a single channel, or all channels.")
+                        }
+                    };
+                    w.awden().set_bit();
+                    w.jawden().set_bit()
+                });
+            }
+
+            /// Disable the analog watchdog.
+            pub fn disable_watchdog(&mut self, _watchdog: AnalogWatchdog) {
+                self.regs.cr1.modify(|_, w| {
+                    w.awden().clear_bit();
+                    w.jawden().clear_bit()
+                });
+            }
+
+            /// Set the analog watchdog's thresholds, in raw 12-bit counts. The watchdog flags
+            /// conversions below `low`, or above `high`. F4 compares these against the
+            /// conversion result before alignment, at 12 bits regardless of resolution.
+            pub fn set_watchdog_thresholds(&mut self, _watchdog: AnalogWatchdog, low: u32, high: u32) {
+                self.regs.ltr.write(|w| unsafe { w.bits(low & 0xfff) });
+                self.regs.htr.write(|w| unsafe { w.bits(high & 0xfff) });
+            }
+
+            /// Set the analog watchdog's thresholds, in volts. `vdda` is the ADC reference voltage.
+            pub fn set_watchdog_thresholds_volts(
+                &mut self,
+                watchdog: AnalogWatchdog,
+                low: f32,
+                high: f32,
+                vdda: f32,
+            ) {
+                let to_counts = |v: f32| {
+                    let counts = v / vdda * FULL_SCALE;
+                    if counts <= 0. {
+                        0
+                    } else if counts >= FULL_SCALE {
+                        FULL_SCALE as u32
+                    } else {
+                        counts as u32
+                    }
+                };
+
+                self.set_watchdog_thresholds(watchdog, to_counts(low), to_counts(high));
+            }
+
+            /// Check if the analog watchdog has flagged a conversion outside its thresholds. Clear
+            /// the flag with `clear_interrupt(watchdog.interrupt())`.
+            pub fn watchdog_triggered(&self, _watchdog: AnalogWatchdog) -> bool {
+                self.regs.sr.read().awd().bit_is_set()
+            }
+
+            /// The `ADC_CR1` interrupt-enable bit, and `ADC_SR` flag bit, for an interrupt.
+            fn interrupt_bits(interrupt: AdcInterrupt) -> (u32, u32) {
+                match interrupt {
+                    AdcInterrupt::EndOfConversion => (1 << 5, 1 << 1),
+                    AdcInterrupt::AnalogWatchdog1 => (1 << 6, 1 << 0),
+                    AdcInterrupt::InjectedEndOfSequence => (1 << 7, 1 << 2),
+                    AdcInterrupt::Overrun => (1 << 26, 1 << 5),
+                    _ => panic!("This ADC interrupt isn't available on F4."),
+                }
+            }
+
+            /// Enable an interrupt. F4 supports `EndOfConversion`, `InjectedEndOfSequence`,
+            /// `AnalogWatchdog1`, and `Overrun`.
+            pub fn enable_interrupt(&mut self, interrupt: AdcInterrupt) {
+                let (ie, _) = Self::interrupt_bits(interrupt);
+                self.regs.cr1.modify(|r, w| unsafe { w.bits(r.bits() | ie) });
+            }
+
+            /// Disable an interrupt.
+            pub fn disable_interrupt(&mut self, interrupt: AdcInterrupt) {
+                let (ie, _) = Self::interrupt_bits(interrupt);
+                self.regs.cr1.modify(|r, w| unsafe { w.bits(r.bits() & !ie) });
+            }
+
+            /// Clear an interrupt flag. Run this in the interrupt handler, so it doesn't
+            /// fire repeatedly.
+            pub fn clear_interrupt(&mut self, interrupt: AdcInterrupt) {
+                let (_, flag) = Self::interrupt_bits(interrupt);
+                // These flags are cleared by writing 0; writing 1 has no effect.
+                self.regs.sr.write(|w| unsafe { w.bits(!flag) });
+            }
+        }
+
+        oneshot!($ADC);
+    }
+}
+
 #[cfg(any(feature = "f301", feature = "f302", feature = "f303",))]
 hal!(ADC1, ADC1_2, adc1, 12);
 
@@ -754,6 +2562,12 @@ hal!(ADC3, ADC3_4, adc3, 34);
 #[cfg(any(feature = "f303"))]
 hal!(ADC4, ADC3_4, adc4, 34);
 
+#[cfg(any(feature = "f302", feature = "f303"))]
+multi_mode!(ADC1, ADC1_2);
+
+#[cfg(any(feature = "f303"))]
+multi_mode!(ADC3, ADC3_4);
+
 #[cfg(any(feature = "l4"))]
 hal!(ADC1, ADC_COMMON, adc1, _);
 
@@ -767,32 +2581,62 @@ hal!(ADC2, ADC_COMMON, adc2, _);
 #[cfg(any(feature = "l4x5", feature = "l4x6",))]
 hal!(ADC3, ADC_COMMON, adc3, _);
 
-// todo Implement ADC3 on H7.
+// Some L4x1 and L4x2 parts (eg L431 and L432) only have ADC1.
+#[cfg(any(feature = "l4x5", feature = "l4x6",))]
+multi_mode!(ADC1, ADC_COMMON);
+
 cfg_if! {
     if #[cfg(feature = "h7")] {
         hal!(ADC1, ADC12_COMMON, adc1, 12);
         hal!(ADC2, ADC12_COMMON, adc2, 12);
-        // hal!(ADC3, ADC3_COMMON, adc3, 3);
+        multi_mode!(ADC1, ADC12_COMMON);
     }
 }
 
+#[cfg(all(feature = "h7", not(feature = "h7b3")))]
+hal!(ADC3, ADC3_COMMON, adc3, 3);
+
 cfg_if! {
     if #[cfg(feature = "g4")] {
         hal!(ADC1, ADC12_COMMON, adc1, 12);
         hal!(ADC2, ADC12_COMMON, adc2, 12);
+        multi_mode!(ADC1, ADC12_COMMON);
     }
 }
 
-// todo: Implement ADCs 3, 4, 5 on G4 variants that support it.
-// cfg_if! {
-//     if #[cfg(any(feature = "g4..."))] {
-//         hal!(ADC3, ADC345_COMMON, adc3, 345);
-//         hal!(ADC4, ADC345_COMMON, adc4, 345);
-//         hal!(ADC5, ADC345_COMMON, adc5, 345);
-//     }
-// }
+#[cfg(any(
+    feature = "g471",
+    feature = "g473",
+    feature = "g474",
+    feature = "g483",
+    feature = "g484",
+    feature = "g491",
+    feature = "g4a1"
+))]
+hal!(ADC3, ADC345_COMMON, adc3, 345);
+
+cfg_if! {
+    if #[cfg(any(feature = "g473", feature = "g474", feature = "g483", feature = "g484"))] {
+        hal!(ADC4, ADC345_COMMON, adc4, 345);
+        hal!(ADC5, ADC345_COMMON, adc5, 345);
+        // ADC3 and ADC4 are a pair; ADC5 has none.
+        multi_mode!(ADC3, ADC345_COMMON);
+    }
+}
 
-// #[cfg(all(feature = "f4")]
-// hal!(ADC1, ADC1, adc1, 1);
+#[cfg(feature = "f4")]
+hal_f4!(ADC1, adc1);
 
-// todo F4 as (depending on variant?) ADC 1, 2, 3
+cfg_if! {
+    if #[cfg(any(
+        feature = "f405",
+        feature = "f407",
+        feature = "f427",
+        feature = "f429",
+        feature = "f446",
+        feature = "f469"
+    ))] {
+        hal_f4!(ADC2, adc2);
+        hal_f4!(ADC3, adc3);
+    }
+}
@@ -0,0 +1,159 @@
+//! Debounced button input, using a GPIO pin configured as an EXTI interrupt source, and a
+//! millisecond time source. This can be `timer::Monotonic` (eg `(mono.now_us() / 1_000) as u32`),
+//! or a counter incremented by a periodic `Timer` interrupt.
+//!
+//! Configure the pin's interrupt with `Edge::Both`, and call `Button::on_interrupt` from its
+//! EXTI handler. Call `Button::poll` periodically, eg from a timer interrupt every few ms,
+//! to get press, release, and long-press events. Neither blocks, so both are safe to call
+//! from interrupt handlers; store the `Button` in a `Mutex<RefCell<>>` to share it.
+//!
+//! Example:
+//! ```ignore
+//! let mut pa0 = gpioa.new_pin(PinNum::P0, PinMode::Input);
+//! pa0.pull(Pull::Up, &mut gpioa.regs);
+//! pa0.enable_interrupt(Edge::Both, &mut dp.EXTI, &mut dp.SYSCFG);
+//! let now_ms = (mono.now_us() / 1_000) as u32;
+//! let mut button = Button::new(pa0.into(), ButtonConfig::default(), now_ms);
+//!
+//! // In the EXTI0 handler:
+//! button.on_interrupt(&mut exti, (mono.now_us() / 1_000) as u32);
+//!
+//! // In a periodic timer handler:
+//! if let Some(event) = button.poll((mono.now_us() / 1_000) as u32) { ... }
+//! ```
+
+use crate::{gpio::Pin, pac::EXTI};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// A debounced button event, returned by `Button::poll`.
+pub enum ButtonEvent {
+    /// The button was pressed.
+    Pressed,
+    /// The button was released.
+    Released,
+    /// The button has been held for `ButtonConfig::long_press_ms`. Reported once per press,
+    /// before its `Released` event.
+    LongPress,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// The pin's input level when the button is pressed.
+pub enum ActiveLevel {
+    /// Pressed when low; eg a button to ground, with a pull-up.
+    Low,
+    /// Pressed when high; eg a button to VCC, with a pull-down.
+    High,
+}
+
+#[derive(Clone, Copy)]
+/// Button configuration. Times are in ms.
+pub struct ButtonConfig {
+    pub active_level: ActiveLevel,
+    /// How long the input must be stable, with no edges, before a change is reported.
+    pub debounce_ms: u32,
+    /// How long the button must be held to report a `LongPress`. 0 disables long presses.
+    pub long_press_ms: u32,
+}
+
+impl Default for ButtonConfig {
+    fn default() -> Self {
+        Self {
+            active_level: ActiveLevel::Low,
+            debounce_ms: 20,
+            long_press_ms: 1_000,
+        }
+    }
+}
+
+/// A debounced button, on a GPIO pin configured as an EXTI interrupt source.
+pub struct Button {
+    pin: Pin,
+    pub config: ButtonConfig,
+    /// Debounced state.
+    pressed: bool,
+    /// Time of the most recent edge that hasn't settled yet.
+    edge_time: Option<u32>,
+    /// Time the current press started.
+    press_time: u32,
+    long_press_reported: bool,
+}
+
+impl Button {
+    /// Create a button. Its initial state is read from the pin, without reporting an event.
+    /// `now_ms` is the current time; if the button is already held, a long press is timed
+    /// from it.
+    pub fn new(pin: Pin, config: ButtonConfig, now_ms: u32) -> Self {
+        let mut result = Self {
+            pin,
+            config,
+            pressed: false,
+            edge_time: None,
+            press_time: now_ms,
+            long_press_reported: false,
+        };
+        result.pressed = result.read_pressed();
+
+        result
+    }
+
+    /// Call this from the pin's EXTI interrupt handler. Clears the pending flag, and records
+    /// the edge. `now_ms` is the current time; it may wrap.
+    pub fn on_interrupt(&mut self, exti: &mut EXTI, now_ms: u32) {
+        self.pin.clear_interrupt(exti);
+        self.edge_time = Some(now_ms);
+    }
+
+    /// Call this periodically, with the current time in ms. Returns an event if the debounced
+    /// state changed, or a long press elapsed, since the last call.
+    pub fn poll(&mut self, now_ms: u32) -> Option<ButtonEvent> {
+        if let Some(edge_time) = self.edge_time {
+            if now_ms.wrapping_sub(edge_time) < self.config.debounce_ms {
+                // Still bouncing.
+                return None;
+            }
+            self.edge_time = None;
+
+            let pressed = self.read_pressed();
+            if pressed != self.pressed {
+                self.pressed = pressed;
+
+                if pressed {
+                    self.press_time = edge_time;
+                    self.long_press_reported = false;
+                    return Some(ButtonEvent::Pressed);
+                } else {
+                    return Some(ButtonEvent::Released);
+                }
+            }
+        }
+
+        if self.pressed
+            && !self.long_press_reported
+            && self.config.long_press_ms != 0
+            && now_ms.wrapping_sub(self.press_time) >= self.config.long_press_ms
+        {
+            self.long_press_reported = true;
+            return Some(ButtonEvent::LongPress);
+        }
+
+        None
+    }
+
+    /// Returns the debounced state.
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    /// Release the pin.
+    pub fn free(self) -> Pin {
+        self.pin
+    }
+
+    /// Read the pin, accounting for the active level.
+    fn read_pressed(&self) -> bool {
+        match self.config.active_level {
+            ActiveLevel::Low => self.pin.is_low(),
+            ActiveLevel::High => self.pin.is_high(),
+        }
+    }
+}
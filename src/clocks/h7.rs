@@ -428,6 +428,10 @@ impl ClockCfg for Clocks {
         }
     }
 
+    fn apb3(&self) -> u32 {
+        self.hclk() / self.d1_prescaler.value() as u32
+    }
+
     fn validate_speeds(&self) -> ClocksValid {
         let mut result = ClocksValid::Valid;
 
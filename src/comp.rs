@@ -0,0 +1,648 @@
+//! Support for the analog comparator (COMP) peripheral. Comparators compare a non-inverting
+//! input (usually a GPIO pin) against an inverting input (a GPIO pin, a DAC channel, or a fraction
+//! of VREFINT), and output a digital signal that can trigger EXTI interrupts and events, or trip
+//! a timer's break input, eg for over-current protection or zero-crossing detection.
+//!
+//! All comparators share one register block. Similar to GPIO pins, each `Comp` represents a single
+//! comparator, and its methods take a reference to the register block.
+//!
+//! See L4 RM, section 22: Comparator (COMP). F3 RM, section 16. G4 RM, section 24. H743 RM,
+//! section 28.
+
+use crate::{
+    gpio::{self, Edge, ExtiMask},
+    pac::{EXTI, RCC},
+};
+
+#[cfg(not(feature = "f3"))]
+use crate::pac::TIM1;
+
+use cfg_if::cfg_if;
+
+cfg_if! {
+    if #[cfg(feature = "h7")] {
+        use crate::pac::COMP1 as COMP;
+    } else {
+        use crate::pac::COMP;
+    }
+}
+
+// Field positions in `COMPx_CSR`. (`COMP_CFGRx` on H7. On F373, both comparators share
+// `COMP_CSR`: these are positions within each comparator's half-word.) Enable is bit 0
+// on all families.
+cfg_if! {
+    if #[cfg(feature = "f373")] {
+        const PWRMODE: u8 = 2;
+        const INMSEL: u8 = 4;
+        const INMSEL_WIDTH: u8 = 3;
+        // WNDWEN is bit 23 of `COMP_CSR`; it's in COMP2's half-word.
+        const WINMODE: u8 = 7;
+        const OUTSEL: u8 = 8;
+        const OUTSEL_WIDTH: u8 = 3;
+        const POL: u8 = 11;
+        const HYST: u8 = 12;
+        const HYST_WIDTH: u8 = 2;
+        const VALUE: u8 = 14;
+        const LOCK: u8 = 15;
+    } else if #[cfg(feature = "f3")] {
+        const PWRMODE: u8 = 2;
+        const INMSEL: u8 = 4;
+        const INMSEL_WIDTH: u8 = 3;
+        // INMSEL[3], used to select DAC2 on some comparators.
+        #[cfg(any(feature = "f303", feature = "f3x4"))]
+        const INMSEL_3: u8 = 22;
+        const INPSEL: u8 = 7;
+        const INPSEL_WIDTH: u8 = 1;
+        const WINMODE: u8 = 9;
+        const OUTSEL: u8 = 10;
+        const OUTSEL_WIDTH: u8 = 4;
+        const POL: u8 = 15;
+        const HYST: u8 = 16;
+        const HYST_WIDTH: u8 = 2;
+        const BLANKING: u8 = 18;
+        const BLANKING_WIDTH: u8 = 3;
+        const VALUE: u8 = 30;
+        const LOCK: u8 = 31;
+    } else if #[cfg(any(feature = "l4", feature = "l5"))] {
+        const PWRMODE: u8 = 2;
+        const INMSEL: u8 = 4;
+        const INMSEL_WIDTH: u8 = 3;
+        const INPSEL: u8 = 7;
+        const INPSEL_WIDTH: u8 = 1;
+        const WINMODE: u8 = 9;
+        const POL: u8 = 15;
+        const HYST: u8 = 16;
+        const HYST_WIDTH: u8 = 2;
+        const BLANKING: u8 = 18;
+        const BLANKING_WIDTH: u8 = 3;
+        const BRGEN: u8 = 22;
+        const SCALEN: u8 = 23;
+        const VALUE: u8 = 30;
+        const LOCK: u8 = 31;
+    } else if #[cfg(feature = "g0")] {
+        const INMSEL: u8 = 4;
+        const INMSEL_WIDTH: u8 = 4;
+        const INPSEL: u8 = 8;
+        const INPSEL_WIDTH: u8 = 2;
+        const WINMODE: u8 = 11;
+        const WINOUT: u8 = 14;
+        const POL: u8 = 15;
+        const HYST: u8 = 16;
+        const HYST_WIDTH: u8 = 2;
+        const PWRMODE: u8 = 18;
+        const BLANKING: u8 = 20;
+        const BLANKING_WIDTH: u8 = 5;
+        const VALUE: u8 = 30;
+        const LOCK: u8 = 31;
+    } else if #[cfg(feature = "g4")] {
+        const INMSEL: u8 = 4;
+        const INMSEL_WIDTH: u8 = 3;
+        const INPSEL: u8 = 8;
+        const INPSEL_WIDTH: u8 = 1;
+        const POL: u8 = 15;
+        const HYST: u8 = 16;
+        const HYST_WIDTH: u8 = 3;
+        const BLANKING: u8 = 19;
+        const BLANKING_WIDTH: u8 = 3;
+        const BRGEN: u8 = 22;
+        const SCALEN: u8 = 23;
+        const VALUE: u8 = 30;
+        const LOCK: u8 = 31;
+    } else { // H7. The output value is in `COMP_SR`.
+        const BRGEN: u8 = 1;
+        const SCALEN: u8 = 2;
+        const POL: u8 = 3;
+        // Only in `COMP_CFGR2`.
+        const WINMODE: u8 = 4;
+        const HYST: u8 = 8;
+        const HYST_WIDTH: u8 = 2;
+        const PWRMODE: u8 = 12;
+        const INMSEL: u8 = 16;
+        const INMSEL_WIDTH: u8 = 3;
+        const INPSEL: u8 = 20;
+        const INPSEL_WIDTH: u8 = 1;
+        const BLANKING: u8 = 24;
+        const BLANKING_WIDTH: u8 = 4;
+        const LOCK: u8 = 31;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[repr(u8)]
+/// Select the comparator. Available comparators vary by MCU.
+pub enum CompNum {
+    #[cfg(not(any(feature = "f301", feature = "f3x4")))]
+    One = 1,
+    Two = 2,
+    #[cfg(any(feature = "f303", feature = "g4"))]
+    Three = 3,
+    #[cfg(any(
+        feature = "f301",
+        feature = "f302",
+        feature = "f303",
+        feature = "f3x4",
+        feature = "g4"
+    ))]
+    Four = 4,
+    #[cfg(any(
+        feature = "f303",
+        feature = "g473",
+        feature = "g474",
+        feature = "g483",
+        feature = "g484"
+    ))]
+    Five = 5,
+    #[cfg(any(
+        feature = "f301",
+        feature = "f302",
+        feature = "f303",
+        feature = "f3x4",
+        feature = "g473",
+        feature = "g474",
+        feature = "g483",
+        feature = "g484"
+    ))]
+    Six = 6,
+    #[cfg(any(
+        feature = "f303",
+        feature = "g473",
+        feature = "g474",
+        feature = "g483",
+        feature = "g484"
+    ))]
+    Seven = 7,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[repr(u8)]
+/// Inverting (minus) input selection. (INMSEL) Which DAC channels and pins connect to each
+/// comparator varies; see the RM table "COMP inputs/outputs summary", or similar. Eg on G4, `DacCh1`
+/// and `DacCh2` select DAC3 or DAC4, and DAC1 or DAC2 respectively, depending on the comparator.
+pub enum InvertingInput {
+    /// 1/4 of VREFINT
+    OneQuarterVref = 0b000,
+    /// 1/2 of VREFINT
+    OneHalfVref = 0b001,
+    /// 3/4 of VREFINT
+    ThreeQuarterVref = 0b010,
+    /// VREFINT
+    Vref = 0b011,
+    /// DAC channel 1 (DAC1_CH1 on most MCUs)
+    DacCh1 = 0b100,
+    /// DAC channel 2 (DAC1_CH2 on most MCUs)
+    DacCh2 = 0b101,
+    /// The comparator's first inverting input pin
+    Io1 = 0b110,
+    #[cfg(not(feature = "f373"))]
+    /// The comparator's second inverting input pin
+    Io2 = 0b111,
+    #[cfg(feature = "f373")]
+    /// DAC2 channel 1
+    Dac2Ch1 = 0b111,
+    #[cfg(any(feature = "f303", feature = "f3x4"))]
+    /// DAC2 channel 1. Only available on some comparators. (INMSEL[3])
+    Dac2Ch1 = 0b1000,
+    #[cfg(feature = "g0")]
+    /// The comparator's third inverting input pin
+    Io3 = 0b1000,
+}
+
+impl InvertingInput {
+    #[cfg(any(feature = "l4", feature = "l5", feature = "g4", feature = "h7"))]
+    /// Returns (scaler bridge enable, voltage scaler enable); ie BRGEN and SCALEN. The VREFINT
+    /// scaler must be enabled when using VREFINT as an input, and the resistor bridge when
+    /// using a fraction of it.
+    fn scaler(&self) -> (bool, bool) {
+        match self {
+            Self::OneQuarterVref | Self::OneHalfVref | Self::ThreeQuarterVref => (true, true),
+            Self::Vref => (false, true),
+            _ => (false, false),
+        }
+    }
+}
+
+#[cfg(not(feature = "f373"))]
+#[derive(Clone, Copy, PartialEq)]
+#[repr(u8)]
+/// Non-inverting (plus) input selection. (INPSEL) Which pin each of these corresponds to
+/// varies by comparator; see the RM or datasheet. On F3, COMP1 doesn't have this setting.
+pub enum NonInvertingInput {
+    /// The comparator's first non-inverting input pin
+    Io1 = 0,
+    /// The comparator's second non-inverting input pin
+    Io2 = 1,
+    #[cfg(feature = "g0")]
+    /// The comparator's third non-inverting input pin
+    Io3 = 2,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[repr(u8)]
+/// Hysteresis, to prevent the output from toggling rapidly when the inputs are close.
+/// (HYST) See the datasheet for voltages.
+pub enum Hysteresis {
+    /// No hysteresis. (Reset value)
+    None = 0,
+    #[cfg(not(feature = "g4"))]
+    Low = 1,
+    #[cfg(not(feature = "g4"))]
+    Medium = 2,
+    #[cfg(not(feature = "g4"))]
+    High = 3,
+    #[cfg(feature = "g4")]
+    Mv10 = 1,
+    #[cfg(feature = "g4")]
+    Mv20 = 2,
+    #[cfg(feature = "g4")]
+    Mv30 = 3,
+    #[cfg(feature = "g4")]
+    Mv40 = 4,
+    #[cfg(feature = "g4")]
+    Mv50 = 5,
+    #[cfg(feature = "g4")]
+    Mv60 = 6,
+    #[cfg(feature = "g4")]
+    Mv70 = 7,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[repr(u8)]
+/// Output polarity. (POL)
+pub enum OutputPolarity {
+    /// The output is high when the non-inverting input is higher than the inverting input.
+    NotInverted = 0,
+    /// The output is low when the non-inverting input is higher than the inverting input.
+    Inverted = 1,
+}
+
+#[cfg(not(feature = "g4"))]
+#[derive(Clone, Copy, PartialEq)]
+#[repr(u8)]
+/// Power mode, trading propagation delay for current consumption. (PWRMODE, or MODE on F3)
+pub enum PowerMode {
+    /// High speed, high power. (Reset value)
+    HighSpeed = 0b00,
+    /// Medium speed, medium power.
+    MediumSpeed = 0b01,
+    #[cfg(feature = "f3")]
+    /// Low speed, low power.
+    LowSpeed = 0b10,
+    #[cfg(not(feature = "g0"))]
+    /// Ultra-low power, and the slowest speed.
+    UltraLow = 0b11,
+}
+
+#[derive(Clone, Copy)]
+/// Comparator configuration.
+pub struct CompConfig {
+    pub inverting: InvertingInput,
+    #[cfg(not(feature = "f373"))]
+    pub non_inverting: NonInvertingInput,
+    pub hysteresis: Hysteresis,
+    pub polarity: OutputPolarity,
+    #[cfg(not(feature = "g4"))]
+    pub power_mode: PowerMode,
+    #[cfg(not(feature = "f373"))]
+    /// Blanking source: The output is masked while the selected timer output is active, eg to
+    /// ignore current spikes when a power switch turns on. (BLANKING, or BLANKSEL) The timer output
+    /// each value selects depends on the comparator; see the RM table "COMPx blanking sources". 0
+    /// disables blanking. (Reset value)
+    pub blanking: u8,
+    #[cfg(feature = "f3")]
+    /// Output selection: Connect the output to a timer's break or input capture input, or
+    /// OCREF_CLR. (OUTSEL) The selected signal depends on the comparator; see the RM register
+    /// description. 0 leaves it unconnected. (Reset value)
+    pub output: u8,
+}
+
+impl Default for CompConfig {
+    fn default() -> Self {
+        Self {
+            inverting: InvertingInput::Vref,
+            #[cfg(not(feature = "f373"))]
+            non_inverting: NonInvertingInput::Io1,
+            hysteresis: Hysteresis::None,
+            polarity: OutputPolarity::NotInverted,
+            #[cfg(not(feature = "g4"))]
+            power_mode: PowerMode::HighSpeed,
+            #[cfg(not(feature = "f373"))]
+            blanking: 0,
+            #[cfg(feature = "f3")]
+            output: 0,
+        }
+    }
+}
+
+/// Run `$body` with `$reg` bound to the comparator's control and status register. Register names
+/// vary by family. (F373's shared `COMP_CSR` is handled separately)
+macro_rules! with_csr {
+    ($num:expr, $regs:expr, |$reg:ident| $body:expr) => {
+        cfg_if! {
+            if #[cfg(feature = "g4")] {
+                match $num {
+                    CompNum::One => { let $reg = &$regs.comp_c1csr; $body }
+                    CompNum::Two => { let $reg = &$regs.comp_c2csr; $body }
+                    CompNum::Three => { let $reg = &$regs.comp_c3csr; $body }
+                    CompNum::Four => { let $reg = &$regs.comp_c4csr; $body }
+                    #[cfg(any(feature = "g473", feature = "g474", feature = "g483", feature = "g484"))]
+                    CompNum::Five => { let $reg = &$regs.comp_c5csr; $body }
+                    #[cfg(any(feature = "g473", feature = "g474", feature = "g483", feature = "g484"))]
+                    CompNum::Six => { let $reg = &$regs.comp_c6csr; $body }
+                    #[cfg(any(feature = "g473", feature = "g474", feature = "g483", feature = "g484"))]
+                    CompNum::Seven => { let $reg = &$regs.comp_c7csr; $body }
+                }
+            } else if #[cfg(feature = "h7b3")] {
+                match $num {
+                    CompNum::One => { let $reg = &$regs.comp1_cfgr1; $body }
+                    CompNum::Two => { let $reg = &$regs.comp1_cfgr2; $body }
+                }
+            } else if #[cfg(feature = "h7")] {
+                match $num {
+                    CompNum::One => { let $reg = &$regs.cfgr1; $body }
+                    CompNum::Two => { let $reg = &$regs.cfgr2; $body }
+                }
+            } else {
+                match $num {
+                    #[cfg(not(any(feature = "f301", feature = "f3x4")))]
+                    CompNum::One => { let $reg = &$regs.comp1_csr; $body }
+                    CompNum::Two => { let $reg = &$regs.comp2_csr; $body }
+                    #[cfg(feature = "f303")]
+                    CompNum::Three => { let $reg = &$regs.comp3_csr; $body }
+                    #[cfg(feature = "f3")]
+                    CompNum::Four => { let $reg = &$regs.comp4_csr; $body }
+                    #[cfg(feature = "f303")]
+                    CompNum::Five => { let $reg = &$regs.comp5_csr; $body }
+                    #[cfg(feature = "f3")]
+                    CompNum::Six => { let $reg = &$regs.comp6_csr; $body }
+                    #[cfg(feature = "f303")]
+                    CompNum::Seven => { let $reg = &$regs.comp7_csr; $body }
+                }
+            }
+        }
+    };
+}
+
+/// Represents a single analog comparator.
+pub struct Comp {
+    pub num: CompNum,
+}
+
+impl Comp {
+    /// Configure a comparator. It's left disabled; run `enable` to start it. Note that the
+    /// comparator, and the VREFINT scaler if used, take some time to start up after enabling;
+    /// see the datasheet for these times.
+    pub fn new(num: CompNum, cfg: &CompConfig, regs: &mut COMP, rcc: &mut RCC) -> Self {
+        // We enable the clock, but don't reset the peripheral, since other comparators may
+        // be in use. On families other than H7, this clock is shared with SYSCFG.
+        cfg_if! {
+            if #[cfg(feature = "h7")] {
+                rcc.apb4enr.modify(|_, w| w.comp12en().set_bit());
+            } else if #[cfg(feature = "g0")] {
+                rcc.apbenr2.modify(|_, w| w.syscfgen().set_bit());
+            } else {
+                rcc.apb2enr.modify(|_, w| w.syscfgen().set_bit());
+            }
+        }
+
+        let mut result = Self { num };
+
+        result.set_inverting_input(cfg.inverting, regs);
+        #[cfg(not(feature = "f373"))]
+        result.set_non_inverting_input(cfg.non_inverting, regs);
+        result.set_hysteresis(cfg.hysteresis, regs);
+        result.set_polarity(cfg.polarity, regs);
+        #[cfg(not(feature = "g4"))]
+        result.set_power_mode(cfg.power_mode, regs);
+        #[cfg(not(feature = "f373"))]
+        result.set_blanking(cfg.blanking, regs);
+        #[cfg(feature = "f3")]
+        result.set_output(cfg.output, regs);
+
+        result
+    }
+
+    #[cfg(feature = "f373")]
+    /// The position of this comparator's half-word in `COMP_CSR`.
+    fn csr_shift(&self) -> u8 {
+        match self.num {
+            CompNum::One => 0,
+            CompNum::Two => 16,
+        }
+    }
+
+    /// Read this comparator's control and status register.
+    fn read_csr(&self, regs: &COMP) -> u32 {
+        cfg_if! {
+            if #[cfg(feature = "f373")] {
+                (regs.csr.read().bits() >> self.csr_shift()) & 0xffff
+            } else {
+                with_csr!(self.num, regs, |csr| csr.read().bits())
+            }
+        }
+    }
+
+    /// Modify this comparator's control and status register, by passing its current value
+    /// to `f`, and writing the result.
+    fn modify_csr<F: FnOnce(u32) -> u32>(&mut self, regs: &mut COMP, f: F) {
+        cfg_if! {
+            if #[cfg(feature = "f373")] {
+                let shift = self.csr_shift();
+                regs.csr.modify(|r, w| unsafe {
+                    let val = f((r.bits() >> shift) & 0xffff) & 0xffff;
+                    w.bits((r.bits() & !(0xffff << shift)) | (val << shift))
+                });
+            } else {
+                with_csr!(self.num, regs, |csr| csr.modify(|r, w| unsafe { w.bits(f(r.bits())) }))
+            }
+        }
+    }
+
+    /// Set a field in this comparator's control and status register.
+    fn set_field(&mut self, regs: &mut COMP, shift: u8, width: u8, val: u32) {
+        let mask = ((1 << width) - 1) << shift;
+        self.modify_csr(regs, |r| (r & !mask) | ((val << shift) & mask));
+    }
+
+    /// Enable the comparator. (EN)
+    pub fn enable(&mut self, regs: &mut COMP) {
+        self.set_field(regs, 0, 1, 1);
+    }
+
+    /// Disable the comparator.
+    pub fn disable(&mut self, regs: &mut COMP) {
+        self.set_field(regs, 0, 1, 0);
+    }
+
+    /// Select the inverting (minus) input. When selecting VREFINT or a fraction of it, this
+    /// enables the VREFINT scaler, and its resistor bridge if required.
+    pub fn set_inverting_input(&mut self, input: InvertingInput, regs: &mut COMP) {
+        let val = input as u32;
+        self.set_field(regs, INMSEL, INMSEL_WIDTH, val);
+
+        #[cfg(any(feature = "f303", feature = "f3x4"))]
+        self.set_field(regs, INMSEL_3, 1, val >> 3);
+
+        #[cfg(any(feature = "l4", feature = "l5", feature = "g4", feature = "h7"))]
+        {
+            let (brgen, scalen) = input.scaler();
+            self.set_field(regs, BRGEN, 1, brgen as u32);
+            self.set_field(regs, SCALEN, 1, scalen as u32);
+        }
+    }
+
+    #[cfg(not(feature = "f373"))]
+    /// Select the non-inverting (plus) input.
+    pub fn set_non_inverting_input(&mut self, input: NonInvertingInput, regs: &mut COMP) {
+        self.set_field(regs, INPSEL, INPSEL_WIDTH, input as u32);
+    }
+
+    /// Set hysteresis.
+    pub fn set_hysteresis(&mut self, hysteresis: Hysteresis, regs: &mut COMP) {
+        self.set_field(regs, HYST, HYST_WIDTH, hysteresis as u32);
+    }
+
+    /// Set output polarity.
+    pub fn set_polarity(&mut self, polarity: OutputPolarity, regs: &mut COMP) {
+        self.set_field(regs, POL, 1, polarity as u32);
+    }
+
+    #[cfg(not(feature = "g4"))]
+    /// Set the power mode.
+    pub fn set_power_mode(&mut self, mode: PowerMode, regs: &mut COMP) {
+        self.set_field(regs, PWRMODE, 2, mode as u32);
+    }
+
+    #[cfg(not(feature = "f373"))]
+    /// Set the blanking source, as the BLANKING field value. See `CompConfig::blanking`.
+    pub fn set_blanking(&mut self, source: u8, regs: &mut COMP) {
+        self.set_field(regs, BLANKING, BLANKING_WIDTH, source as u32);
+    }
+
+    #[cfg(feature = "f3")]
+    /// Connect the output to a timer input, as the OUTSEL field value. See `CompConfig::output`.
+    pub fn set_output(&mut self, output: u8, regs: &mut COMP) {
+        self.set_field(regs, OUTSEL, OUTSEL_WIDTH, output as u32);
+    }
+
+    #[cfg(not(feature = "g4"))]
+    /// Enable or disable window mode, where this comparator's non-inverting input is connected
+    /// to that of the other comparator in its pair, so the two can check if a signal is between
+    /// two thresholds. Run this on the even-numbered comparator of the pair; eg COMP2, for COMP1
+    /// and COMP2. On G0, run it on the comparator whose input should be replaced. (WINMODE)
+    pub fn set_window_mode(&mut self, enable: bool, regs: &mut COMP) {
+        self.set_field(regs, WINMODE, 1, enable as u32);
+    }
+
+    #[cfg(feature = "g0")]
+    /// Output the window comparator result (the XOR of both comparators' outputs) on this
+    /// comparator's output, instead of its own value. (WINOUT)
+    pub fn set_window_output(&mut self, enable: bool, regs: &mut COMP) {
+        self.set_field(regs, WINOUT, 1, enable as u32);
+    }
+
+    /// Read the comparator's output level, after polarity is applied. `true` means high.
+    pub fn output_level(&self, regs: &COMP) -> bool {
+        cfg_if! {
+            if #[cfg(feature = "h7b3")] {
+                regs.comp1_sr.read().bits() & (1 << (self.num as u8 - 1)) != 0
+            } else if #[cfg(feature = "h7")] {
+                regs.sr.read().bits() & (1 << (self.num as u8 - 1)) != 0
+            } else {
+                self.read_csr(regs) & (1 << VALUE) != 0
+            }
+        }
+    }
+
+    /// Lock the comparator's configuration. Its control register becomes read-only until the
+    /// MCU is reset, eg to protect an over-current trip from errant writes. (LOCK)
+    pub fn lock(&mut self, regs: &mut COMP) {
+        self.set_field(regs, LOCK, 1, 1);
+    }
+
+    /// Check if the comparator's configuration is locked.
+    pub fn is_locked(&self, regs: &COMP) -> bool {
+        self.read_csr(regs) & (1 << LOCK) != 0
+    }
+
+    /// The EXTI line this comparator's output is connected to. See the RM table "EXTI lines
+    /// connections", or similar.
+    pub fn exti_line(&self) -> u8 {
+        let num = self.num as u8;
+
+        cfg_if! {
+            if #[cfg(any(feature = "g0", feature = "l5"))] {
+                16 + num
+            } else if #[cfg(feature = "h7")] {
+                19 + num
+            } else {
+                // COMP1 and COMP2 are on lines 21 and 22; COMP3 to COMP7 on lines 29 to 33.
+                if num <= 2 {
+                    20 + num
+                } else {
+                    26 + num
+                }
+            }
+        }
+    }
+
+    /// Trigger an interrupt on the selected output edge, using the comparator's EXTI line. Set
+    /// up the interrupt handler for the COMP interrupt on your MCU; eg `COMP` on L4, or
+    /// `COMP1_2_3` on F303.
+    pub fn enable_interrupt(&mut self, edge: Edge, exti: &mut EXTI) {
+        let (rise, fall) = edge.triggers();
+        gpio::exti_edges(exti, self.exti_line(), rise, fall);
+        gpio::exti_mask(exti, ExtiMask::Interrupt, self.exti_line(), true);
+    }
+
+    /// Generate an event on the selected output edge, eg to wake from `WFE`.
+    pub fn enable_event(&mut self, edge: Edge, exti: &mut EXTI) {
+        let (rise, fall) = edge.triggers();
+        gpio::exti_edges(exti, self.exti_line(), rise, fall);
+        gpio::exti_mask(exti, ExtiMask::Event, self.exti_line(), true);
+    }
+
+    /// Stop the comparator from triggering interrupts.
+    pub fn disable_interrupt(&mut self, exti: &mut EXTI) {
+        gpio::exti_mask(exti, ExtiMask::Interrupt, self.exti_line(), false);
+    }
+
+    /// Check if the comparator's EXTI line has an interrupt pending.
+    pub fn is_interrupt_pending(&self, exti: &EXTI) -> bool {
+        gpio::exti_line_pending(exti, self.exti_line())
+    }
+
+    /// Clear the comparator's EXTI line pending flag. Call this in the interrupt handler.
+    pub fn clear_interrupt(&mut self, exti: &mut EXTI) {
+        gpio::exti_clear_pending(exti, self.exti_line());
+    }
+
+    #[cfg(not(feature = "f3"))]
+    /// Connect the output to TIM1's break input, eg to disable PWM outputs on over-current,
+    /// without software intervention. Enable break in the timer's BDTR register too. (TIM1_AF1,
+    /// or TIM1_OR2 on L4 and L5: BKCMPxE) On F3, use `set_output` instead.
+    pub fn enable_break(&mut self, tim: &mut TIM1) {
+        let bit = 1 << self.num as u8;
+
+        cfg_if! {
+            if #[cfg(any(feature = "l4", feature = "l5"))] {
+                tim.or2.modify(|r, w| unsafe { w.bits(r.bits() | bit) });
+            } else {
+                tim.af1.modify(|r, w| unsafe { w.bits(r.bits() | bit) });
+            }
+        }
+    }
+
+    #[cfg(not(feature = "f3"))]
+    /// Disconnect the output from TIM1's break input.
+    pub fn disable_break(&mut self, tim: &mut TIM1) {
+        let bit = 1 << self.num as u8;
+
+        cfg_if! {
+            if #[cfg(any(feature = "l4", feature = "l5"))] {
+                tim.or2.modify(|r, w| unsafe { w.bits(r.bits() & !bit) });
+            } else {
+                tim.af1.modify(|r, w| unsafe { w.bits(r.bits() & !bit) });
+            }
+        }
+    }
+}
@@ -1,18 +1,26 @@
 //! Support for the digital to Analog converter peripheral.
+//!
+//! `Dac` is generic over its register block, eg `Dac<pac::DAC1>`, since some MCUs have several
+//! DACs. This is a breaking change from the earlier non-generic `Dac`: Add the type parameter
+//! to existing type annotations. `Dac::new` is unchanged.
 
-// Note that we don't use macros hereto the same extent as with other modules,
-// since all families appear to only have a single DAC register block. For example,
-// the `Dac` struct doesn't accept a trait of its reg block. We may have to
-// change this later as we find exceptions.
-
-// Some MCUs (F3 and G4?) use a second DAC interface - this is currently not
+// Some MCUs have more than one DAC: F303 and F3x4 have DAC1 and DAC2, and G4 has DAC1 - 4,
+// where DAC3 and DAC4 only connect to on-chip peripherals, like comparators and opamps.
+// The single-channel DAC2 on F301 and F373 uses a different register block, and isn't
 // implemented.
 
+#[cfg(not(any(feature = "f3", feature = "f4", feature = "g0")))]
+use cortex_m::asm;
+
+#[cfg(not(any(feature = "f4", feature = "g0")))]
 use crate::{
     pac::{self, RCC},
     rcc_en_reset,
 };
 
+#[cfg(not(any(feature = "f3", feature = "f4", feature = "g0")))]
+use crate::traits::ClockCfg;
+
 use cfg_if::cfg_if;
 
 // #[derive(Clone, Copy)]
@@ -76,17 +84,84 @@ impl Trigger {
     }
 }
 
+#[cfg(any(
+    feature = "l4",
+    feature = "l5",
+    feature = "g4",
+    all(feature = "h7", not(feature = "h7b3"))
+))]
+#[derive(Clone, Copy)]
+#[repr(u8)]
+/// Output mode: Where the output connects, whether the output buffer is used, and whether
+/// sample-and-hold is enabled. (DAC_MCR MODEx) The buffer lets the output drive low-impedance
+/// loads, at the cost of power.
+pub enum DacMode {
+    /// Normal mode, connected to the external pin, with the buffer enabled. (Reset value)
+    NormalPinBuffered = 0b000,
+    /// Normal mode, connected to the external pin and on-chip peripherals, with the buffer enabled.
+    NormalPinChipBuffered = 0b001,
+    /// Normal mode, connected to the external pin, with the buffer disabled.
+    NormalPin = 0b010,
+    /// Normal mode, connected to on-chip peripherals, with the buffer disabled.
+    NormalChip = 0b011,
+    /// Sample-and-hold mode, connected to the external pin, with the buffer enabled.
+    SampleHoldPinBuffered = 0b100,
+    /// Sample-and-hold mode, connected to the external pin and on-chip peripherals, with the
+    /// buffer enabled.
+    SampleHoldPinChipBuffered = 0b101,
+    /// Sample-and-hold mode, connected to the external pin and on-chip peripherals, with the
+    /// buffer disabled.
+    SampleHoldPinChip = 0b110,
+    /// Sample-and-hold mode, connected to on-chip peripherals, with the buffer disabled.
+    SampleHoldChip = 0b111,
+}
+
+#[cfg(any(
+    feature = "l4",
+    feature = "l5",
+    feature = "g4",
+    all(feature = "h7", not(feature = "h7b3"))
+))]
+#[derive(Clone, Copy)]
+/// Sample-and-hold timing, in LSI clock cycles. In sample-and-hold mode, the DAC only powers up
+/// periodically to refresh a voltage held on a capacitor, saving power. The LSI must be running.
+/// See the RM section "DAC channel sample and hold mode" for how to choose these values.
+pub struct SampleHoldCfg {
+    /// Sampling phase duration. (TSAMPLE; 10 bits)
+    pub sample_time: u16,
+    /// Hold phase duration. (THOLD; 10 bits)
+    pub hold_time: u16,
+    /// Refresh phase duration. (TREFRESH; 8 bits)
+    pub refresh_time: u8,
+}
+
+#[cfg(not(any(feature = "f4", feature = "g0")))]
+/// Digital to Analog converter peripheral
+pub struct Dac<R> {
+    regs: R,
+    channel: Channel,
+    bits: DacBits,
+    vref: f32,
+}
+
+#[cfg(not(any(feature = "f4", feature = "g0")))]
 // note that L5 uses a different register names, hence the verbose macro. ( eg`dac_cr`)
 macro_rules! hal {
-    ($DAC:ident, $cr:ident, $d81:ident, $d12l1:ident, $d12r1:ident, $d82:ident, $d12l2:ident, $d12r2:ident) => {
-        /// Digital to Analog converter peripheral
-        pub struct Dac {
-            regs: pac::$DAC,
-            channel: Channel,
-            bits: DacBits,
-            vref: f32,
-        }
-        impl Dac {
+    (
+        $DAC:ident,
+        $rcc_en:ident,
+        $cr:ident,
+        $d81:ident,
+        $d12l1:ident,
+        $d12r1:ident,
+        $d82:ident,
+        $d12l2:ident,
+        $d12r2:ident
+        $(, dual: [$d8rd:ident, $d12ld:ident, $d12rd:ident])?
+        $(, sh: [$mcr:ident, $shsr1:ident, $shsr2:ident, $shhr:ident, $shrr:ident, $ccr:ident, $sr:ident])?
+        $(, internal: $internal:literal)?
+    ) => {
+        impl Dac<pac::$DAC> {
             /// Create a new DAC instance.
             // pub fn new<P: GpioPin>(regs: DAC, pin: P, channel: Channel, bits: Bits, vref: f32) -> Self {
             //     // todo: Check for a valid pin too.
@@ -109,20 +184,33 @@ macro_rules! hal {
             ) -> Self {
                 cfg_if! {
                     if #[cfg(all(feature = "h7", not(feature = "h7b3")))] {
-                        rcc_en_reset!(apb1, dac12, rcc);
+                        rcc_en_reset!(apb1, $rcc_en, rcc);
                     } else if #[cfg(feature = "g4")] {
-                        rcc_en_reset!(ahb2, dac1, rcc);
+                        rcc_en_reset!(ahb2, $rcc_en, rcc);
                     } else {
-                        rcc_en_reset!(apb1, dac1, rcc);
+                        rcc_en_reset!(apb1, $rcc_en, rcc);
                     }
                 }
 
-                Self {
+                let result = Self {
                     regs,
                     channel,
                     bits,
                     vref,
-                }
+                };
+
+                // G4's DAC3 and DAC4 only connect to on-chip peripherals, so set both channels' mode
+                // to that: Normal mode, connected to on-chip peripherals, with the buffer disabled.
+                // (DAC_MCR MODE1: bits 0:2, MODE2: bits 16:18)
+                $(
+                    if $internal {
+                        result.regs.dac_mcr.modify(|r, w| unsafe {
+                            w.bits((r.bits() & !(0b111 << 16 | 0b111)) | (0b011 << 16 | 0b011))
+                        });
+                    }
+                )?
+
+                result
             }
 
             /// Enable the DAC.
@@ -159,14 +247,18 @@ macro_rules! hal {
 
             /// Set the DAC voltage. `v` is in Volts.
             pub fn set_voltage(&mut self, volts: f32) {
+                let val = self.volts_to_value(volts);
+                self.set_value(val);
+            }
+
+            /// Convert a voltage to a value to write to a right-aligned holding register.
+            fn volts_to_value(&self, volts: f32) -> u32 {
                 // todo: should these be 256 and 4096?
-                let val = match self.bits {
+                match self.bits {
                     DacBits::EightR => ((volts / self.vref) * 255.) as u32,
                     DacBits::TwelveL => ((volts / self.vref) * 4_095.) as u32,
                     DacBits::TwelveR => ((volts / self.vref) * 4_095.) as u32,
-                };
-
-                self.set_value(val);
+                }
             }
 
             // todo: Trouble finding right `tsel` fields for l5. RM shows same as others. PAC bug?
@@ -230,44 +322,305 @@ macro_rules! hal {
                 self.set_value(data);
             }
         }
+
+        $(
+            impl Dac<pac::$DAC> {
+                /// Enable both channels, for use with `set_dual_value`.
+                pub fn enable_dual(&mut self) {
+                    self.regs.$cr.modify(|_, w| w.en1().set_bit().en2().set_bit());
+                }
+
+                /// Disable both channels.
+                pub fn disable_dual(&mut self) {
+                    self.regs.$cr.modify(|_, w| w.en1().clear_bit().en2().clear_bit());
+                }
+
+                /// Set both channels' values in a single write, using the dual holding registers, so
+                /// they update together, eg for I/Q outputs, or X/Y displays. Uses the same precision and
+                /// alignment as `set_value`: Pass right-aligned values for `TwelveL`, as with the others.
+                pub fn set_dual_value(&mut self, val1: u32, val2: u32) {
+                    match self.bits {
+                        // DACC1DHR: bits 0:7, DACC2DHR: bits 8:15
+                        DacBits::EightR => self.regs.$d8rd.write(|w| unsafe {
+                            w.bits((val1 & 0xff) | ((val2 & 0xff) << 8))
+                        }),
+                        // DACC1DHR: bits 4:15, DACC2DHR: bits 20:31
+                        DacBits::TwelveL => self.regs.$d12ld.write(|w| unsafe {
+                            w.bits(((val1 & 0xfff) << 4) | ((val2 & 0xfff) << 20))
+                        }),
+                        // DACC1DHR: bits 0:11, DACC2DHR: bits 16:27
+                        DacBits::TwelveR => self.regs.$d12rd.write(|w| unsafe {
+                            w.bits((val1 & 0xfff) | ((val2 & 0xfff) << 16))
+                        }),
+                    }
+                }
+
+                /// Set both channels' voltages in a single write. `volts1` and `volts2` are in Volts.
+                pub fn set_dual_voltage(&mut self, volts1: f32, volts2: f32) {
+                    let val1 = self.volts_to_value(volts1);
+                    let val2 = self.volts_to_value(volts2);
+
+                    self.set_dual_value(val1, val2);
+                }
+            }
+        )?
+
+        $(
+            impl Dac<pac::$DAC> {
+                /// Set the output mode: Buffer, connection, and sample-and-hold. Disables the
+                /// channel while changing it, since MODEx is only writable with ENx = 0.
+                pub fn set_mode(&mut self, mode: DacMode) {
+                    // MODE1: bits 0:2, MODE2: bits 16:18
+                    let shift = match self.channel {
+                        Channel::One => 0,
+                        Channel::Two => 16,
+                    };
+                    let was_enabled = self.is_enabled();
+                    self.disable();
+
+                    self.regs.$mcr.modify(|r, w| unsafe {
+                        w.bits((r.bits() & !(0b111 << shift)) | ((mode as u32) << shift))
+                    });
+
+                    if was_enabled {
+                        self.enable();
+                    }
+                }
+
+                /// Check if the channel is enabled.
+                fn is_enabled(&self) -> bool {
+                    let cr = self.regs.$cr.read();
+                    match self.channel {
+                        Channel::One => cr.en1().bit_is_set(),
+                        Channel::Two => cr.en2().bit_is_set(),
+                    }
+                }
+
+                /// Set sample-and-hold timing. Then, select one of the sample-and-hold modes with
+                /// `set_mode`.
+                pub fn set_sample_hold(&mut self, cfg: SampleHoldCfg) {
+                    let (shift, bwst) = match self.channel {
+                        Channel::One => (0, 15),
+                        Channel::Two => (16, 31),
+                    };
+
+                    // RM: TSAMPLE can only be written when BWSTx = 0.
+                    while self.regs.$sr.read().bits() & (1 << bwst) != 0 {}
+
+                    unsafe {
+                        match self.channel {
+                            Channel::One => self.regs.$shsr1.write(|w| w.bits(cfg.sample_time as u32 & 0x3ff)),
+                            Channel::Two => self.regs.$shsr2.write(|w| w.bits(cfg.sample_time as u32 & 0x3ff)),
+                        }
+
+                        self.regs.$shhr.modify(|r, w| {
+                            w.bits((r.bits() & !(0x3ff << shift)) | ((cfg.hold_time as u32 & 0x3ff) << shift))
+                        });
+                        self.regs.$shrr.modify(|r, w| {
+                            w.bits((r.bits() & !(0xff << shift)) | ((cfg.refresh_time as u32) << shift))
+                        });
+                    }
+                }
+
+                /// Set the offset trimming value directly, eg one saved from `calibrate_offset`.
+                /// (DAC_CCR OTRIMx; 5 bits)
+                pub fn set_offset_trim(&mut self, trim: u8) {
+                    let shift = match self.channel {
+                        Channel::One => 0,
+                        Channel::Two => 16,
+                    };
+
+                    self.regs.$ccr.modify(|r, w| unsafe {
+                        w.bits((r.bits() & !(0x1f << shift)) | (((trim & 0x1f) as u32) << shift))
+                    });
+                }
+
+                /// Run the offset trimming calibration, so `set_voltage` is accurate without
+                /// external calibration. Factory trimming is done at 3.0V and 25°C; run this again
+                /// if the supply or temperature differ significantly. This uses the mode set with
+                /// `set_mode`, which must have the buffer enabled. Returns the trim value, which
+                /// can be restored later with `set_offset_trim`, and leaves the channel disabled.
+                /// See the RM section "DAC channel offset trimming".
+                pub fn calibrate_offset<C: ClockCfg>(&mut self, clocks: &C) -> u8 {
+                    let (cen, cal_flag) = match self.channel {
+                        Channel::One => (14, 14),
+                        Channel::Two => (30, 30),
+                    };
+
+                    // RM: t_TRIM is 50µs max.
+                    let trim_delay = clocks.sysclk() / 1_000_000 * 50;
+
+                    // 1. Disable the channel, and enter calibration mode by setting CENx.
+                    self.disable();
+                    self.regs.$cr.modify(|r, w| unsafe { w.bits(r.bits() | (1 << cen)) });
+
+                    // 2. RM: "Apply a trimming offset value OTRIMx starting from 00000" and step it up
+                    // until CAL_FLAGx is set; that value minimizes the offset. If the flag never
+                    // sets, use the largest value.
+                    let mut trim = 0;
+                    while trim < 0x1f {
+                        self.set_offset_trim(trim);
+                        asm::delay(trim_delay);
+
+                        if self.regs.$sr.read().bits() & (1 << cal_flag) != 0 {
+                            break;
+                        }
+                        trim += 1;
+                    }
+                    self.set_offset_trim(trim);
+
+                    // 3. Exit calibration mode.
+                    self.regs.$cr.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << cen)) });
+
+                    trim
+                }
+            }
+        )?
     };
 }
 
 #[cfg(feature = "l5")]
 hal!(
     DAC,
+    dac1,
     dac_cr,
+    dac_dhr8r1,
+    dac_dhr12l1,
+    dac_dhr12r1,
     dac_dhr8r2,
     dac_dhr12l2,
     dac_dhr12r2,
-    dac_dhr8r2,
-    dac_dhr12l2,
-    dac_dhr12r2
+    dual: [dac_dhr8rd, dac_dhr12ld, dac_dhr12rd],
+    sh: [dac_mcr, dac_shsr1, dac_shsr2, dac_shhr, dac_shrr, dac_ccr, dac_sr]
 );
 
 #[cfg(all(feature = "l4", not(feature = "l4x6")))]
-hal!(DAC1, cr, dhr8r1, dhr12l1, dhr12r1, dhr8r2, dhr12l2, dhr12r2);
+hal!(
+    DAC1,
+    dac1,
+    cr,
+    dhr8r1,
+    dhr12l1,
+    dhr12r1,
+    dhr8r2,
+    dhr12l2,
+    dhr12r2,
+    dual: [dhr8rd, dhr12ld, dhr12rd],
+    sh: [mcr, shsr1, shsr2, shhr, shrr, ccr, sr]
+);
 
 #[cfg(feature = "l4x6")]
-hal!(DAC, cr, dhr8r1, dhr12l1, dhr12r1, dhr8r2, dhr12l2, dhr12r2);
+hal!(
+    DAC,
+    dac1,
+    cr,
+    dhr8r1,
+    dhr12l1,
+    dhr12r1,
+    dhr8r2,
+    dhr12l2,
+    dhr12r2,
+    dual: [dhr8rd, dhr12ld, dhr12rd],
+    sh: [mcr, shsr1, shsr2, shhr, shrr, ccr, sr]
+);
 
 #[cfg(all(feature = "f3", not(feature = "f302")))]
-hal!(DAC1, cr, dhr8r1, dhr12l1, dhr12r1, dhr8r2, dhr12l2, dhr12r2);
+hal!(
+    DAC1,
+    dac1,
+    cr,
+    dhr8r1,
+    dhr12l1,
+    dhr12r1,
+    dhr8r2,
+    dhr12l2,
+    dhr12r2,
+    dual: [dhr8rd, dhr12ld, dhr12rd]
+);
 
-#[cfg(feature = "f302")]
-hal!(DAC, cr, dhr8r1, dhr12l1, dhr12r1, dhr8r2, dhr12l2, dhr12r2);
+// DAC2 only has a single channel, so it doesn't get the dual-channel methods.
+#[cfg(any(feature = "f303", feature = "f3x4"))]
+hal!(DAC2, dac2, cr, dhr8r1, dhr12l1, dhr12r1, dhr8r2, dhr12l2, dhr12r2);
 
-#[cfg(feature = "g4")] // Same as L5, but DAC1.
+#[cfg(feature = "f302")]
 hal!(
-    DAC1,
-    dac_cr,
-    dac_dhr8r2,
-    dac_dhr12l2,
-    dac_dhr12r2,
-    dac_dhr8r2,
-    dac_dhr12l2,
-    dac_dhr12r2
+    DAC,
+    dac1,
+    cr,
+    dhr8r1,
+    dhr12l1,
+    dhr12r1,
+    dhr8r2,
+    dhr12l2,
+    dhr12r2,
+    dual: [dhr8rd, dhr12ld, dhr12rd]
 );
 
+#[cfg(feature = "g4")] // Same register names as L5.
+macro_rules! hal_g4 {
+    ($DAC:ident, $rcc_en:ident, $internal:literal) => {
+        hal!(
+            $DAC,
+            $rcc_en,
+            dac_cr,
+            dac_dhr8r1,
+            dac_dhr12l1,
+            dac_dhr12r1,
+            dac_dhr8r2,
+            dac_dhr12l2,
+            dac_dhr12r2,
+            dual: [dac_dhr8rd, dac_dhr12ld, dac_dhr12rd],
+            sh: [dac_mcr, dac_shsr1, dac_shsr2, dac_shhr, dac_shrr, dac_ccr, dac_sr],
+            internal: $internal
+        );
+    };
+}
+
+cfg_if! {
+    if #[cfg(feature = "g4")] {
+        hal_g4!(DAC1, dac1, false);
+        hal_g4!(DAC3, dac3, true);
+    }
+}
+
+// DAC2 and DAC4 are only on G47x and G48x. DAC2 only has a single channel, so it doesn't get
+// the dual-channel methods.
+cfg_if! {
+    if #[cfg(any(
+        feature = "g471",
+        feature = "g473",
+        feature = "g474",
+        feature = "g483",
+        feature = "g484"
+    ))] {
+        hal!(
+            DAC2,
+            dac2,
+            dac_cr,
+            dac_dhr8r1,
+            dac_dhr12l1,
+            dac_dhr12r1,
+            dac_dhr8r2,
+            dac_dhr12l2,
+            dac_dhr12r2,
+            sh: [dac_mcr, dac_shsr1, dac_shsr2, dac_shhr, dac_shrr, dac_ccr, dac_sr],
+            internal: false
+        );
+        hal_g4!(DAC4, dac4, true);
+    }
+}
+
 #[cfg(all(feature = "h7", not(feature = "h7b3")))] // todo h7b3?
-hal!(DAC, cr, dhr8r1, dhr12l1, dhr12r1, dhr8r2, dhr12l2, dhr12r2);
+hal!(
+    DAC,
+    dac12,
+    cr,
+    dhr8r1,
+    dhr12l1,
+    dhr12r1,
+    dhr8r2,
+    dhr12l2,
+    dhr12r2,
+    dual: [dhr8rd, dhr12ld, dhr12rd],
+    sh: [mcr, shsr1, shsr2, shhr, shrr, ccr, sr]
+);
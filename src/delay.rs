@@ -1,10 +1,13 @@
-//! Hardware delays, using Cortex-m systick.
+//! Hardware delays, using Cortex-m systick, or the DWT cycle counter. `Timer`s can also be used
+//! as delay providers; see `timer.rs`. These leave SysTick free, eg for use by an RTOS.
 
 // Based on `stm32l4xx-hal`.
 
 use cast::u32;
 use cortex_m::peripheral::syst::SystClkSource;
 use cortex_m::peripheral::SYST;
+#[cfg(not(feature = "g0"))]
+use cortex_m::peripheral::{DCB, DWT};
 use embedded_hal::blocking::delay::{DelayMs, DelayUs};
 
 use crate::traits::ClockCfg;
@@ -28,16 +31,21 @@ impl Delay {
 
     /// Delay using the Cortex-M systick for a certain duration, ms.
     pub fn delay_ms(&mut self, ms: u32) {
-        self.delay_us(ms * 1_000);
+        self.delay_ticks(ms as u64 * self.systick_speed as u64 / 1_000);
     }
 
-    /// Delay using the Cortex-M systick for a certain duration, µs. This is the core delay
-    /// code all other functions, including the EH trait ones call indirectly.
+    /// Delay using the Cortex-M systick for a certain duration, µs.
     pub fn delay_us(&mut self, us: u32) {
+        self.delay_ticks(us as u64 * self.systick_speed as u64 / 1_000_000);
+    }
+
+    /// Delay for a number of systick ticks. This is the core delay code all other
+    /// functions, including the EH trait ones call indirectly.
+    fn delay_ticks(&mut self, ticks: u64) {
         // The SysTick Reload Value register supports values between 1 and 0x00FFFFFF.
-        const MAX_RVR: u32 = 0x00FF_FFFF;
+        const MAX_RVR: u64 = 0x00FF_FFFF;
 
-        let mut total_rvr = us * (self.systick_speed / 1_000_000);
+        let mut total_rvr = ticks;
 
         while total_rvr != 0 {
             let current_rvr = if total_rvr <= MAX_RVR {
@@ -46,7 +54,7 @@ impl Delay {
                 MAX_RVR
             };
 
-            self.syst.set_reload(current_rvr);
+            self.syst.set_reload(current_rvr as u32);
             self.syst.clear_current();
             self.syst.enable_counter();
 
@@ -101,3 +109,103 @@ impl DelayUs<u8> for Delay {
         Delay::delay_us(self, u32(us));
     }
 }
+
+#[cfg(not(feature = "g0"))]
+/// DWT cycle counter as a delay provider. This doesn't take ownership of SysTick, and has
+/// single-cycle resolution, so it's suitable for short delays, eg bit-banged protocols.
+/// Not available on Cortex-M0+ MCUs (eg G0), which don't have a cycle counter. It uses the
+/// debug and trace block (DCB and DWT), so don't use it while a debugger or trace tool
+/// reconfigures those.
+pub struct DwtDelay {
+    hclk: u32, // Core clock speed, in Hz.
+}
+
+#[cfg(not(feature = "g0"))]
+impl DwtDelay {
+    /// Enable the DWT cycle counter, and configure it as a delay provider.
+    pub fn new<C: ClockCfg>(dcb: &mut DCB, dwt: &mut DWT, clocks: &C) -> Self {
+        // The cycle counter requires trace to be enabled.
+        dcb.enable_trace();
+        // Cortex-M7 cores (eg H7) lock the DWT after a reset; the cycle counter doesn't run
+        // until it's unlocked.
+        DWT::unlock();
+        dwt.enable_cycle_counter();
+
+        Self {
+            hclk: clocks.hclk(),
+        }
+    }
+
+    /// Delay for a certain duration, ms.
+    pub fn delay_ms(&mut self, ms: u32) {
+        self.delay_cycles(ms as u64 * self.hclk as u64 / 1_000);
+    }
+
+    /// Delay for a certain duration, µs.
+    pub fn delay_us(&mut self, us: u32) {
+        self.delay_cycles(us as u64 * self.hclk as u64 / 1_000_000);
+    }
+
+    /// Delay for a certain duration, ns. Resolution is one core clock cycle; the call overhead
+    /// adds a few cycles, so very short delays will run long.
+    pub fn delay_ns(&mut self, ns: u32) {
+        self.delay_cycles(ns as u64 * self.hclk as u64 / 1_000_000_000);
+    }
+
+    /// Delay for a number of core clock cycles.
+    pub fn delay_cycles(&mut self, cycles: u64) {
+        let mut remaining = cycles;
+
+        // The counter is 32 bits, and wraps; wait in chunks that fit in half its range.
+        while remaining != 0 {
+            let chunk = remaining.min(u32::MAX as u64 / 2) as u32;
+            let start = DWT::cycle_count();
+
+            while DWT::cycle_count().wrapping_sub(start) < chunk {}
+
+            remaining -= chunk as u64;
+        }
+    }
+}
+
+#[cfg(not(feature = "g0"))]
+impl DelayMs<u32> for DwtDelay {
+    fn delay_ms(&mut self, ms: u32) {
+        DwtDelay::delay_ms(self, ms);
+    }
+}
+
+#[cfg(not(feature = "g0"))]
+impl DelayMs<u16> for DwtDelay {
+    fn delay_ms(&mut self, ms: u16) {
+        DwtDelay::delay_ms(self, u32(ms));
+    }
+}
+
+#[cfg(not(feature = "g0"))]
+impl DelayMs<u8> for DwtDelay {
+    fn delay_ms(&mut self, ms: u8) {
+        DwtDelay::delay_ms(self, u32(ms));
+    }
+}
+
+#[cfg(not(feature = "g0"))]
+impl DelayUs<u32> for DwtDelay {
+    fn delay_us(&mut self, us: u32) {
+        DwtDelay::delay_us(self, us);
+    }
+}
+
+#[cfg(not(feature = "g0"))]
+impl DelayUs<u16> for DwtDelay {
+    fn delay_us(&mut self, us: u16) {
+        DwtDelay::delay_us(self, u32(us));
+    }
+}
+
+#[cfg(not(feature = "g0"))]
+impl DelayUs<u8> for DwtDelay {
+    fn delay_us(&mut self, us: u8) {
+        DwtDelay::delay_us(self, u32(us));
+    }
+}
@@ -2,7 +2,7 @@
 //! including all GPIOx register functions, and interrupts.
 //! It includes implementations of `embedded-hal` pin abstraction.
 
-use core::convert::Infallible;
+use core::{convert::Infallible, marker::PhantomData};
 
 use crate::{
     pac::{self, EXTI, RCC},
@@ -12,7 +12,7 @@ use crate::{
 #[cfg(not(any(feature = "l5", feature = "g0")))]
 use crate::pac::SYSCFG;
 
-use embedded_hal::digital::v2::{InputPin, OutputPin, ToggleableOutputPin};
+use embedded_hal::digital::v2::{InputPin, OutputPin, StatefulOutputPin, ToggleableOutputPin};
 
 use cfg_if::cfg_if;
 use paste::paste;
@@ -82,6 +82,15 @@ pub enum CfgLock {
     Locked = 1,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// Errors from locking a pin configuration with `GPIOx_LCKR`.
+pub enum LockError {
+    /// The port is already locked. Its lock configuration can't be changed until the next reset.
+    AlreadyLocked,
+    /// The lock key sequence completed, but `LCKK` or a pin's lock bit didn't read back as set.
+    NotLocked,
+}
+
 #[derive(Copy, Clone)]
 #[repr(u8)]
 /// Values for `GPIOx_AFRL` and `GPIOx_AFRH`.
@@ -162,13 +171,85 @@ pub enum PinNum {
     P15,
 }
 
+impl PinNum {
+    /// Convert from a pin number. Panics (at compile time, when used in a const context)
+    /// if `n` is over 15.
+    const fn from_u8(n: u8) -> Self {
+        match n {
+            0 => Self::P0,
+            1 => Self::P1,
+            2 => Self::P2,
+            3 => Self::P3,
+            4 => Self::P4,
+            5 => Self::P5,
+            6 => Self::P6,
+            7 => Self::P7,
+            8 => Self::P8,
+            9 => Self::P9,
+            10 => Self::P10,
+            11 => Self::P11,
+            12 => Self::P12,
+            13 => Self::P13,
+            14 => Self::P14,
+            15 => Self::P15,
+            _ => panic!("Pin number must be 0 through 15"),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 // A pulse edge, used to trigger interrupts.
 pub enum Edge {
     Rising,
     Falling,
+    Both,
 }
 
+impl Edge {
+    /// Returns (rising trigger enabled, falling trigger enabled); ie `EXTI_RTSR` and `EXTI_FTSR` bits.
+    pub(crate) fn triggers(&self) -> (bool, bool) {
+        match self {
+            Self::Rising => (true, false),
+            Self::Falling => (false, true),
+            Self::Both => (true, true),
+        }
+    }
+}
+
+// Typestate markers, for use with `GpioXTypedPin`. These track a pin's mode at compile time,
+// so eg an input pin can't be set high.
+
+/// Typestate: Input mode, with a pull resistor configuration. (eg `Input<PullUp>`)
+pub struct Input<PULL> {
+    _pull: PhantomData<PULL>,
+}
+
+/// Typestate: Output mode, with an output type. (eg `Output<PushPull>`)
+pub struct Output<TYPE> {
+    _type: PhantomData<TYPE>,
+}
+
+/// Typestate: Alternate function mode, with the AF number. (eg `Alternate<7>`)
+pub struct Alternate<const AF: u8>;
+
+/// Typestate: Analog mode
+pub struct Analog;
+
+/// Typestate: Floating input
+pub struct Floating;
+
+/// Typestate: Pulled-up input
+pub struct PullUp;
+
+/// Typestate: Pulled-down input
+pub struct PullDown;
+
+/// Typestate: Push-pull output
+pub struct PushPull;
+
+/// Typestate: Open-drain output
+pub struct OpenDrain;
+
 // pub struct GpioError {}
 
 // // todo: Should this trait be in `traits.rs` (or eventually crate) ?
@@ -192,9 +273,12 @@ macro_rules! make_port {
         paste! {
             /// Represents a single GPIO port, and owns its register block. Provides
             /// methods to enable the port. To change pin properties, pass its `regs`
-            /// field as a mutable reference to `GpioXPin` methods.
+            /// field as a mutable reference to `GpioXPin` methods. To read, write, or configure
+            /// multiple pins at once, use its methods that take a pin mask.
             pub struct [<Gpio $Port>] {
                 pub regs: pac::[<GPIO $Port>],
+                /// Pins handed out by `new_typed_pin`, one bit per pin.
+                typed_taken: u16,
             }
 
             impl [<Gpio $Port>] {
@@ -219,7 +303,10 @@ macro_rules! make_port {
                         }
                     }
 
-                    Self { regs }
+                    Self {
+                        regs,
+                        typed_taken: 0,
+                    }
                 }
 
                 pub fn new_pin(&mut self, pin: PinNum, mode: PinMode) -> [<Gpio $Port Pin>] {
@@ -231,11 +318,161 @@ macro_rules! make_port {
 
                     result
                 }
+
+                /// Create a pin whose mode is tracked at compile time, eg `new_typed_pin::<5>()`
+                /// for P*5. It starts as a floating input; use its `into_` methods to change mode.
+                /// Each pin is only handed out once, so its tracked mode can't be changed through
+                /// another handle; this returns `None` if the pin has already been taken.
+                pub fn new_typed_pin<const N: u8>(&mut self) -> Option<[<Gpio $Port TypedPin>]<N, Input<Floating>>> {
+                    let mask = 1 << [<Gpio $Port TypedPin>]::<N, Input<Floating>>::PIN as u8;
+                    if self.typed_taken & mask != 0 {
+                        return None;
+                    }
+                    self.typed_taken |= mask;
+
+                    let pin = [<Gpio $Port TypedPin>]::<N, Input<Floating>> {
+                        _mode: PhantomData,
+                    };
+                    let mut result = pin.into_dynamic();
+                    result.mode(PinMode::Input, &mut self.regs);
+                    result.pull(Pull::Floating, &mut self.regs);
+
+                    Some([<Gpio $Port TypedPin>] {
+                        _mode: PhantomData,
+                    })
+                }
+
+                /// Set and clear multiple pins in a single write to `GPIOx_BSRR`, so they change
+                /// at the same time. Pins whose bit in `mask` is set are set to their bit in `value`;
+                /// others are unchanged. Eg for a parallel bus on pins 0-7:
+                /// `write_masked(0x00ff, data as u16)`.
+                pub fn write_masked(&mut self, mask: u16, value: u16) {
+                    let set = value & mask;
+                    let reset = !value & mask;
+                    self.regs.bsrr.write(|w| unsafe { w.bits(set as u32 | ((reset as u32) << 16)) });
+                }
+
+                /// Read the input levels of multiple pins in a single read of `GPIOx_IDR`. Bits
+                /// not set in `mask` are returned as 0.
+                pub fn read_masked(&self, mask: u16) -> u16 {
+                    self.regs.idr.read().bits() as u16 & mask
+                }
+
+                /// Set the mode of all pins whose bit in `mask` is set. If setting an alternate
+                /// function, it's set before the mode, to prevent glitches.
+                pub fn set_mode(&mut self, mask: u16, value: PinMode) {
+                    let regs = &self.regs;
+                    cortex_m::interrupt::free(|_| {
+                        if let PinMode::Alt(alt) = value {
+                            let (afrl_mask, afrl_val) = spread_field(mask & 0xff, 4, alt as u8);
+                            let (afrh_mask, afrh_val) = spread_field(mask >> 8, 4, alt as u8);
+                            regs.afrl.modify(|r, w| unsafe { w.bits((r.bits() & !afrl_mask) | afrl_val) });
+                            regs.afrh.modify(|r, w| unsafe { w.bits((r.bits() & !afrh_mask) | afrh_val) });
+                        }
+
+                        let (field_mask, val) = spread_field(mask, 2, value.val());
+                        regs.moder.modify(|r, w| unsafe { w.bits((r.bits() & !field_mask) | val) });
+                    });
+                }
+
+                /// Set the output type of all pins whose bit in `mask` is set.
+                pub fn set_output_type(&mut self, mask: u16, value: OutputType) {
+                    let regs = &self.regs;
+                    cortex_m::interrupt::free(|_| {
+                        regs.otyper.modify(|r, w| unsafe {
+                            match value {
+                                OutputType::PushPull => w.bits(r.bits() & !(mask as u32)),
+                                OutputType::OpenDrain => w.bits(r.bits() | mask as u32),
+                            }
+                        });
+                    });
+                }
+
+                /// Set the output speed of all pins whose bit in `mask` is set.
+                pub fn set_output_speed(&mut self, mask: u16, value: OutputSpeed) {
+                    let regs = &self.regs;
+                    let (field_mask, val) = spread_field(mask, 2, value as u8);
+                    cortex_m::interrupt::free(|_| {
+                        regs.ospeedr.modify(|r, w| unsafe { w.bits((r.bits() & !field_mask) | val) });
+                    });
+                }
+
+                #[cfg(not(feature = "f373"))]
+                /// Lock the configuration of all pins whose bit in `mask` is set, until the next
+                /// reset. This can only be done once per port per reset.
+                pub fn lock(&mut self, mask: u16) -> Result<(), LockError> {
+                    lock_pins!(self.regs, mask)
+                }
+
+                #[cfg(not(feature = "f373"))]
+                /// Check if this port's configuration lock is active. (`LCKK` bit)
+                pub fn is_locked(&self) -> bool {
+                    self.regs.lckr.read().bits() & LCKK != 0
+                }
+
+                /// Set the pull resistor configuration of all pins whose bit in `mask` is set.
+                pub fn set_pull(&mut self, mask: u16, value: Pull) {
+                    let regs = &self.regs;
+                    let (field_mask, val) = spread_field(mask, 2, value as u8);
+                    cortex_m::interrupt::free(|_| {
+                        regs.pupdr.modify(|r, w| unsafe { w.bits((r.bits() & !field_mask) | val) });
+                    });
+                }
             }
         }
     };
 }
 
+/// For registers with a field of `width` bits per pin (eg 2 for `GPIOx_MODER`, 4 for
+/// `GPIOx_AFRL`), return a mask covering the fields of pins set in `pins`, and `val` placed in
+/// each of those fields.
+fn spread_field(pins: u16, width: u8, val: u8) -> (u32, u32) {
+    let field = (1 << width) - 1;
+    let mut mask = 0;
+    let mut result = 0;
+
+    for i in 0..(32 / width) {
+        if pins & (1 << i) != 0 {
+            mask |= field << (i * width);
+            result |= (val as u32 & field) << (i * width);
+        }
+    }
+    (mask, result)
+}
+
+/// `GPIOx_LCKR` lock key bit.
+#[cfg(not(feature = "f373"))]
+const LCKK: u32 = 1 << 16;
+
+/// Lock the pins set in `$mask`, using the `GPIOx_LCKR` key sequence: Write `LCKK` = 1, 0, 1,
+/// with the same pin bits each time, then read. Then read again to verify the lock took effect.
+/// Evaluates to `Result<(), LockError>`.
+#[cfg(not(feature = "f373"))]
+macro_rules! lock_pins {
+    ($regs:expr, $mask:expr) => {{
+        let mask = $mask as u32;
+
+        if $regs.lckr.read().bits() & LCKK != 0 {
+            Err(LockError::AlreadyLocked)
+        } else {
+            // The sequence is aborted if interrupted by a different write to LCKR.
+            cortex_m::interrupt::free(|_| unsafe {
+                $regs.lckr.write(|w| w.bits(LCKK | mask));
+                $regs.lckr.write(|w| w.bits(mask));
+                $regs.lckr.write(|w| w.bits(LCKK | mask));
+                $regs.lckr.read();
+            });
+
+            let lckr = $regs.lckr.read().bits();
+            if lckr & LCKK != 0 && lckr & mask == mask {
+                Ok(())
+            } else {
+                Err(LockError::NotLocked)
+            }
+        }
+    }};
+}
+
 /// Reduce DRY for setting fields.
 macro_rules! set_field {
     ($pin:expr, $regs:expr, $reg:ident, $field:ident, $bit:ident, $val:expr, [$($num:expr),+]) => {
@@ -254,32 +491,21 @@ macro_rules! set_field {
 
 // todo: Consolidate these exti macros
 
-/// Reduce DRY for setting up interrupts.
+/// Reduce DRY for setting up interrupts and events: Sets the EXTI line's edge triggers,
+/// and selects the port. Unmasking is handled separately.
 macro_rules! set_exti {
-    ($pin:expr, $exti:expr, $syscfg:expr, $trigger:expr, $val:expr, [$(($num:expr, $crnum:expr)),+]) => {
+    ($pin:expr, $exti:expr, $syscfg:expr, $rise:expr, $fall:expr, $val:expr, [$(($num:expr, $crnum:expr)),+]) => {
         paste! {
             match $pin {
                 $(
                     PinNum::[<P $num>] => {
-                        cfg_if! {
-                            if #[cfg(all(feature = "h7", not(any(feature = "h747cm4", feature = "h747cm7"))))] {
-                                $exti.cpuimr1.modify(|_, w| w.[<mr $num>]().unmasked());
-                            } else if #[cfg(any(feature = "h747cm4", feature = "h747cm7"))] {
-                                $exti.c1imr1.modify(|_, w| w.[<mr $num>]().unmasked());
-                            }else if #[cfg(feature = "g4")] {
-                                $exti.imr1.modify(|_, w| w.[<im $num>]().unmasked());
-                            } else {
-                                $exti.imr1.modify(|_, w| w.[<mr $num>]().unmasked());
-                            }
-                        }
-
                         cfg_if! {
                             if #[cfg(feature = "g4")] {
-                                $exti.rtsr1.modify(|_, w| w.[<rt $num>]().bit($trigger));
-                                $exti.ftsr1.modify(|_, w| w.[<ft $num>]().bit(!$trigger));
+                                $exti.rtsr1.modify(|_, w| w.[<rt $num>]().bit($rise));
+                                $exti.ftsr1.modify(|_, w| w.[<ft $num>]().bit($fall));
                             } else {
-                                $exti.rtsr1.modify(|_, w| w.[<tr $num>]().bit($trigger));
-                                $exti.ftsr1.modify(|_, w| w.[<tr $num>]().bit(!$trigger));
+                                $exti.rtsr1.modify(|_, w| w.[<tr $num>]().bit($rise));
+                                $exti.ftsr1.modify(|_, w| w.[<tr $num>]().bit($fall));
                             }
                         }
                         $syscfg
@@ -295,14 +521,13 @@ macro_rules! set_exti {
 #[cfg(feature = "f4")]
 /// Similar to `set_exti`, but with reg names sans `1`.
 macro_rules! set_exti_f4 {
-    ($pin:expr, $exti:expr, $syscfg:expr, $trigger:expr, $val:expr, [$(($num:expr, $crnum:expr)),+]) => {
+    ($pin:expr, $exti:expr, $syscfg:expr, $rise:expr, $fall:expr, $val:expr, [$(($num:expr, $crnum:expr)),+]) => {
         paste! {
             match $pin {
                 $(
                     PinNum::[<P $num>] => {
-                        $exti.imr.modify(|_, w| w.[<mr $num>]().unmasked());
-                        $exti.rtsr.modify(|_, w| w.[<tr $num>]().bit($trigger));
-                        $exti.ftsr.modify(|_, w| w.[<tr $num>]().bit(!$trigger));
+                        $exti.rtsr.modify(|_, w| w.[<tr $num>]().bit($rise));
+                        $exti.ftsr.modify(|_, w| w.[<tr $num>]().bit($fall));
                         $syscfg
                             .[<exticr $crnum>]
                             .modify(|_, w| unsafe { w.[<exti $num>]().bits($val) });
@@ -316,14 +541,13 @@ macro_rules! set_exti_f4 {
 #[cfg(feature = "l5")]
 /// For L5 See `set_exti!`. Different method naming pattern for exticr.
 macro_rules! set_exti_l5 {
-    ($pin:expr, $exti:expr, $trigger:expr, $val:expr, [$(($num:expr, $crnum:expr, $num2:expr)),+]) => {
+    ($pin:expr, $exti:expr, $rise:expr, $fall:expr, $val:expr, [$(($num:expr, $crnum:expr, $num2:expr)),+]) => {
         paste! {
             match $pin {
                 $(
                     PinNum::[<P $num>] => {
-                        $exti.imr1.modify(|_, w| w.[<im $num>]().set_bit());  // unmask
-                        $exti.rtsr1.modify(|_, w| w.[<rt $num>]().bit($trigger));  // Rising trigger
-                        $exti.ftsr1.modify(|_, w| w.[<ft $num>]().bit(!$trigger));   // Falling trigger
+                        $exti.rtsr1.modify(|_, w| w.[<rt $num>]().bit($rise));  // Rising trigger
+                        $exti.ftsr1.modify(|_, w| w.[<ft $num>]().bit($fall));   // Falling trigger
                         $exti
                             .[<exticr $crnum>]
                             .modify(|_, w| unsafe { w.[<exti $num2>]().bits($val) });
@@ -337,14 +561,13 @@ macro_rules! set_exti_l5 {
 #[cfg(feature = "g0")]
 /// ForG0. See `set_exti!`. Todo? Reduce DRY.
 macro_rules! set_exti_g0 {
-    ($pin:expr, $exti:expr, $trigger:expr, $val:expr, [$(($num:expr, $crnum:expr, $num2:expr)),+]) => {
+    ($pin:expr, $exti:expr, $rise:expr, $fall:expr, $val:expr, [$(($num:expr, $crnum:expr, $num2:expr)),+]) => {
         paste! {
             match $pin {
                 $(
                     PinNum::[<P $num>] => {
-                        $exti.imr1.modify(|_, w| w.[<im $num>]().set_bit());  // unmask
-                        $exti.rtsr1.modify(|_, w| w.[<tr $num>]().bit($trigger));  // Rising trigger
-                        $exti.ftsr1.modify(|_, w| w.[<tr $num>]().bit(!$trigger));   // Falling trigger
+                        $exti.rtsr1.modify(|_, w| w.[<tr $num>]().bit($rise));  // Rising trigger
+                        $exti.ftsr1.modify(|_, w| w.[<tr $num>]().bit($fall));   // Falling trigger
                         $exti
                             .[<exticr $crnum>]
                             .modify(|_, w| unsafe { w.[<exti $num2>]().bits($val) });
@@ -355,6 +578,179 @@ macro_rules! set_exti_g0 {
     }
 }
 
+/// Which EXTI mask register to modify.
+#[derive(Clone, Copy)]
+pub(crate) enum ExtiMask {
+    /// `EXTI_IMR`: Interrupt mask register
+    Interrupt,
+    /// `EXTI_EMR`: Event mask register
+    Event,
+}
+
+/// Unmask (`enable` = true) or mask an EXTI line in the interrupt or event mask register.
+/// Lines 0-15 are the GPIO lines, and share register 1 on families that have more than one.
+/// Lines 32 and up, used by some internal peripherals like comparators, are in register 2.
+pub(crate) fn exti_mask(exti: &mut EXTI, reg: ExtiMask, line: u8, enable: bool) {
+    let update = |bits: u32| {
+        if enable {
+            bits | (1 << (line % 32))
+        } else {
+            bits & !(1 << (line % 32))
+        }
+    };
+
+    unsafe {
+        cfg_if! {
+            if #[cfg(any(feature = "f4", feature = "f373"))] {
+                match reg {
+                    ExtiMask::Interrupt => exti.imr.modify(|r, w| w.bits(update(r.bits()))),
+                    ExtiMask::Event => exti.emr.modify(|r, w| w.bits(update(r.bits()))),
+                }
+            } else if #[cfg(any(feature = "h747cm4", feature = "h747cm7"))] {
+                match reg {
+                    ExtiMask::Interrupt => exti.c1imr1.modify(|r, w| w.bits(update(r.bits()))),
+                    ExtiMask::Event => exti.c1emr1.modify(|r, w| w.bits(update(r.bits()))),
+                }
+            } else if #[cfg(feature = "h7")] {
+                match reg {
+                    ExtiMask::Interrupt => exti.cpuimr1.modify(|r, w| w.bits(update(r.bits()))),
+                    ExtiMask::Event => exti.cpuemr1.modify(|r, w| w.bits(update(r.bits()))),
+                }
+            } else if #[cfg(feature = "g0")] {
+                match reg {
+                    ExtiMask::Interrupt => exti.imr1.modify(|r, w| w.bits(update(r.bits()))),
+                    ExtiMask::Event => exti.emr1.modify(|r, w| w.bits(update(r.bits()))),
+                }
+            } else {
+                match (reg, line < 32) {
+                    (ExtiMask::Interrupt, true) => exti.imr1.modify(|r, w| w.bits(update(r.bits()))),
+                    (ExtiMask::Event, true) => exti.emr1.modify(|r, w| w.bits(update(r.bits()))),
+                    (ExtiMask::Interrupt, false) => exti.imr2.modify(|r, w| w.bits(update(r.bits()))),
+                    (ExtiMask::Event, false) => exti.emr2.modify(|r, w| w.bits(update(r.bits()))),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(any(
+    feature = "f4",
+    feature = "g030",
+    feature = "g031",
+    feature = "g041",
+    feature = "g070"
+)))]
+/// Set the rising and falling edge triggers of an EXTI line that isn't tied to a GPIO pin, eg
+/// a comparator output. Only configurable lines have these triggers; see the RM's EXTI line
+/// connections table. Lines 32 and up are in register 2.
+pub(crate) fn exti_edges(exti: &mut EXTI, line: u8, rise: bool, fall: bool) {
+    let update = |bits: u32, enable: bool| {
+        if enable {
+            bits | (1 << (line % 32))
+        } else {
+            bits & !(1 << (line % 32))
+        }
+    };
+
+    unsafe {
+        cfg_if! {
+            if #[cfg(feature = "f373")] {
+                exti.rtsr.modify(|r, w| w.bits(update(r.bits(), rise)));
+                exti.ftsr.modify(|r, w| w.bits(update(r.bits(), fall)));
+            } else if #[cfg(any(feature = "g0", feature = "h7"))] {
+                exti.rtsr1.modify(|r, w| w.bits(update(r.bits(), rise)));
+                exti.ftsr1.modify(|r, w| w.bits(update(r.bits(), fall)));
+            } else {
+                if line < 32 {
+                    exti.rtsr1.modify(|r, w| w.bits(update(r.bits(), rise)));
+                    exti.ftsr1.modify(|r, w| w.bits(update(r.bits(), fall)));
+                } else {
+                    exti.rtsr2.modify(|r, w| w.bits(update(r.bits(), rise)));
+                    exti.ftsr2.modify(|r, w| w.bits(update(r.bits(), fall)));
+                }
+            }
+        }
+    }
+}
+
+/// Read the EXTI pending register(s) for lines 0-31. On G0 and L5, rising and falling edges
+/// have separate pending registers (`EXTI_RPR1` and `EXTI_FPR1`); we combine them.
+fn exti_pending(exti: &EXTI) -> u32 {
+    cfg_if! {
+        if #[cfg(any(feature = "g0", feature = "l5"))] {
+            exti.rpr1.read().bits() | exti.fpr1.read().bits()
+        } else if #[cfg(any(feature = "f4", feature = "f373"))] {
+            exti.pr.read().bits()
+        } else if #[cfg(any(feature = "h747cm4", feature = "h747cm7"))] {
+            exti.c1pr1.read().bits()
+        } else if #[cfg(feature = "h7")] {
+            exti.cpupr1.read().bits()
+        } else {
+            exti.pr1.read().bits()
+        }
+    }
+}
+
+#[cfg(not(any(
+    feature = "f4",
+    feature = "g030",
+    feature = "g031",
+    feature = "g041",
+    feature = "g070"
+)))]
+/// Check if an EXTI line has an interrupt pending. Unlike `exti_pending`, this handles lines
+/// 32 and up, which are in register 2.
+pub(crate) fn exti_line_pending(exti: &EXTI, line: u8) -> bool {
+    cfg_if! {
+        if #[cfg(any(feature = "f373", feature = "g0", feature = "h7", feature = "l5"))] {
+            // Only the first pending register is handled on these families.
+            debug_assert!(line < 32);
+            exti_pending(exti) & (1 << (line % 32)) != 0
+        } else {
+            if line < 32 {
+                exti.pr1.read().bits() & (1 << line) != 0
+            } else {
+                exti.pr2.read().bits() & (1 << (line - 32)) != 0
+            }
+        }
+    }
+}
+
+/// Clear an EXTI line's pending flag. These are cleared by writing 1, so we write instead of
+/// modifying, to avoid clearing other lines' flags.
+pub(crate) fn exti_clear_pending(exti: &mut EXTI, line: u8) {
+    // Only the first pending register is handled outside the `else` arm below.
+    #[cfg(any(
+        feature = "g0",
+        feature = "l5",
+        feature = "f4",
+        feature = "f373",
+        feature = "h7"
+    ))]
+    debug_assert!(line < 32);
+
+    unsafe {
+        cfg_if! {
+            if #[cfg(any(feature = "g0", feature = "l5"))] {
+                exti.rpr1.write(|w| w.bits(1 << (line % 32)));
+                exti.fpr1.write(|w| w.bits(1 << (line % 32)));
+            } else if #[cfg(any(feature = "f4", feature = "f373"))] {
+                exti.pr.write(|w| w.bits(1 << (line % 32)));
+            } else if #[cfg(any(feature = "h747cm4", feature = "h747cm7"))] {
+                exti.c1pr1.write(|w| w.bits(1 << (line % 32)));
+            } else if #[cfg(feature = "h7")] {
+                exti.cpupr1.write(|w| w.bits(1 << (line % 32)));
+            } else {
+                if line < 32 {
+                    exti.pr1.write(|w| w.bits(1 << line));
+                } else {
+                    exti.pr2.write(|w| w.bits(1 << (line - 32)));
+                }
+            }
+        }
+    }
+}
+
 /// Reduce DRY for setting up alternate functions. Note that there are at least 3
 /// different names for the `afrl` field to modify based on variants.
 macro_rules! set_alt {
@@ -377,6 +773,18 @@ macro_rules! set_alt {
     }
 }
 
+/// Create aliases for typed pins, eg `PA5<Output<PushPull>>`.
+macro_rules! make_pin_aliases {
+    ($Port:ident, [$($num:literal),+]) => {
+        paste! {
+            $(
+                #[doc = "Typed pin P" $Port $num]
+                pub type [<P $Port $num>]<MODE> = [<Gpio $Port TypedPin>]<$num, MODE>;
+            )+
+        }
+    };
+}
+
 macro_rules! make_pin {
     ($Port:ident) => {
         paste! {
@@ -423,11 +831,21 @@ macro_rules! make_pin {
 
             // It appears f373 doesn't have lckr on ports C or E.
             #[cfg(not(feature = "f373"))]
+            #[deprecated(note = "This only sets the LCKR bit, without the key sequence, so it doesn't lock the pin. Use `lock` instead.")]
             /// Lock or unlock a port configuration.
             pub fn cfg_lock(&mut self, value: CfgLock, regs: &mut pac::[<GPIO $Port>]) {
                 set_field!(self.pin, regs, lckr, lck, bit, value as u8 != 0, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
             }
 
+            #[cfg(not(feature = "f373"))]
+            /// Lock this pin's configuration (mode, output type, speed, pull, and alternate
+            /// function) until the next reset. The lock key sequence locks the whole port's lock
+            /// configuration, so after this, no other pins on the port can be locked; to lock
+            /// several pins, use `GpioX::lock` with a mask instead.
+            pub fn lock(&mut self, regs: &mut pac::[<GPIO $Port>]) -> Result<(), LockError> {
+                lock_pins!(regs, 1 << self.pin as u8)
+            }
+
             /// Read the input data register.
             pub fn input_data(&mut self, regs: &mut pac::[<GPIO $Port>]) -> PinState {
                 let val = match self.pin {
@@ -539,53 +957,58 @@ macro_rules! make_pin {
                 if #[cfg(any(feature = "g0", feature = "l5"))] {
                     /// Configure this pin as an interrupt source.
                     pub fn enable_interrupt(&mut self, edge: Edge, exti: &mut EXTI) {
-                        // todo: On newer ones, don't accept SYSCFG for this function.
-                        let rise_trigger = match edge {
-                            Edge::Rising => {
-                                // configure EXTI line to trigger on rising edge, disable trigger on falling edge.
-                                true
-                            }
-                            Edge::Falling => {
-                                // configure EXTI line to trigger on falling edge, disable trigger on rising edge.
-                                false
-                            }
-                        };
+                        self.set_exti_line(edge, exti);
+                        exti_mask(exti, ExtiMask::Interrupt, self.pin as u8, true);
+                    }
+
+                    /// Configure this pin as an event source, eg to wake from `WFE`. This doesn't
+                    /// trigger an interrupt, or set a pending flag.
+                    pub fn enable_event(&mut self, edge: Edge, exti: &mut EXTI) {
+                        self.set_exti_line(edge, exti);
+                        exti_mask(exti, ExtiMask::Event, self.pin as u8, true);
+                    }
+
+                    /// Set the EXTI line's edge triggers, and select this port for it.
+                    fn set_exti_line(&self, edge: Edge, exti: &mut EXTI) {
+                        let (rise, fall) = edge.triggers();
 
                         #[cfg(feature = "g0")]
-                        set_exti_g0!(self.pin, exti, rise_trigger, self.port.cr_val(), [(0, 1, 0_7), (1, 1, 0_7), (2, 1, 0_7),
+                        set_exti_g0!(self.pin, exti, rise, fall, self.port.cr_val(), [(0, 1, 0_7), (1, 1, 0_7), (2, 1, 0_7),
                             (3, 1, 0_7), (4, 2, 0_7), (5, 2, 0_7), (6, 2, 0_7), (7, 2, 0_7), (8, 3, 8_15),
                             (9, 3, 8_15), (10, 3, 8_15), (11, 3, 8_15), (12, 4, 8_15),
                             (13, 4, 8_15), (14, 4, 8_15), (15, 4, 8_15)]);
 
                         #[cfg(feature = "l5")]
-                        set_exti_l5!(self.pin, exti, rise_trigger, self.port.cr_val(), [(0, 1, 0_7), (1, 1, 0_7), (2, 1, 0_7),
+                        set_exti_l5!(self.pin, exti, rise, fall, self.port.cr_val(), [(0, 1, 0_7), (1, 1, 0_7), (2, 1, 0_7),
                             (3, 1, 0_7), (4, 2, 0_7), (5, 2, 0_7), (6, 2, 0_7), (7, 2, 0_7), (8, 3, 8_15),
                             (9, 3, 8_15), (10, 3, 8_15), (11, 3, 8_15), (12, 4, 8_15),
                             (13, 4, 8_15), (14, 4, 8_15), (15, 4, 8_15)]);
-
                     }
                 } else if #[cfg(not(feature = "f373"))] {
                     /// Configure this pin as an interrupt source.
                     pub fn enable_interrupt(&mut self, edge: Edge, exti: &mut EXTI, syscfg: &mut SYSCFG) {
-                        // todo: On newer ones, don't accept SYSCFG for this function.
-                        let rise_trigger = match edge {
-                            Edge::Rising => {
-                                // configure EXTI line to trigger on rising edge, disable trigger on falling edge.
-                                true
-                            }
-                            Edge::Falling => {
-                                // configure EXTI line to trigger on falling edge, disable trigger on rising edge.
-                                false
-                            }
-                        };
+                        self.set_exti_line(edge, exti, syscfg);
+                        exti_mask(exti, ExtiMask::Interrupt, self.pin as u8, true);
+                    }
+
+                    /// Configure this pin as an event source, eg to wake from `WFE`. This doesn't
+                    /// trigger an interrupt, or set a pending flag.
+                    pub fn enable_event(&mut self, edge: Edge, exti: &mut EXTI, syscfg: &mut SYSCFG) {
+                        self.set_exti_line(edge, exti, syscfg);
+                        exti_mask(exti, ExtiMask::Event, self.pin as u8, true);
+                    }
+
+                    /// Set the EXTI line's edge triggers, and select this port for it.
+                    fn set_exti_line(&self, edge: Edge, exti: &mut EXTI, syscfg: &mut SYSCFG) {
+                        let (rise, fall) = edge.triggers();
 
                         cfg_if! {
                             if #[cfg(feature = "f4")] {
-                                set_exti_f4!(self.pin, exti, syscfg, rise_trigger, self.port.cr_val(), [(0, 1), (1, 1), (2, 1),
+                                set_exti_f4!(self.pin, exti, syscfg, rise, fall, self.port.cr_val(), [(0, 1), (1, 1), (2, 1),
                                     (3, 1), (4, 2), (5, 2), (6, 2), (7, 2), (8, 3), (9, 3), (10, 3), (11, 3), (12, 4),
                                     (13, 4), (14, 4), (15, 4)])
                             } else {
-                                set_exti!(self.pin, exti, syscfg, rise_trigger, self.port.cr_val(), [(0, 1), (1, 1), (2, 1),
+                                set_exti!(self.pin, exti, syscfg, rise, fall, self.port.cr_val(), [(0, 1), (1, 1), (2, 1),
                                     (3, 1), (4, 2), (5, 2), (6, 2), (7, 2), (8, 3), (9, 3), (10, 3), (11, 3), (12, 4),
                                     (13, 4), (14, 4), (15, 4)])
                             }
@@ -594,6 +1017,29 @@ macro_rules! make_pin {
                 }
             }
 
+            /// Stop this pin from triggering interrupts, by masking its EXTI line. Its edge
+            /// triggers are left configured.
+            pub fn disable_interrupt(&mut self, exti: &mut EXTI) {
+                exti_mask(exti, ExtiMask::Interrupt, self.pin as u8, false);
+            }
+
+            /// Stop this pin from generating events.
+            pub fn disable_event(&mut self, exti: &mut EXTI) {
+                exti_mask(exti, ExtiMask::Event, self.pin as u8, false);
+            }
+
+            /// Check if this pin's EXTI line has an interrupt pending. Note that all ports share
+            /// the EXTI line for a given pin number.
+            pub fn is_interrupt_pending(&self, exti: &EXTI) -> bool {
+                exti_pending(exti) & (1 << self.pin as u8) != 0
+            }
+
+            /// Clear this pin's EXTI line pending flag. Call this in the interrupt handler,
+            /// or the interrupt will fire again on exit.
+            pub fn clear_interrupt(&mut self, exti: &mut EXTI) {
+                exti_clear_pending(exti, self.pin as u8);
+            }
+
             /// Check if the pin's input voltage is high (VCC).
             pub fn is_high(&self) -> bool {
                 // todo: DRy with `input_data`.
@@ -719,7 +1165,226 @@ macro_rules! make_pin {
                 Ok(())
             }
         }
+
+        impl From<[<Gpio $Port Pin>]> for Pin {
+            fn from(pin: [<Gpio $Port Pin>]) -> Self {
+                Self {
+                    port: pin.port,
+                    pin: pin.pin,
+                }
+            }
         }
+
+        /// A GPIO pin whose number, `N`, and mode are tracked at compile time, using the `MODE`
+        /// typestate (eg `Output<PushPull>`). Mode conversions consume the pin, and return one
+        /// with the new mode. Methods that don't apply to the current mode aren't available.
+        /// Registers are accessed directly, so methods don't require the port's register block.
+        pub struct [<Gpio $Port TypedPin>]<const N: u8, MODE> {
+            _mode: PhantomData<MODE>,
+        }
+
+        impl<const N: u8, MODE> [<Gpio $Port TypedPin>]<N, MODE> {
+            // Evaluated at compile time, so an invalid `N` fails to build.
+            const PIN: PinNum = PinNum::from_u8(N);
+
+            /// Return the pin number.
+            pub fn pin(&self) -> PinNum {
+                Self::PIN
+            }
+
+            /// Convert to a floating input.
+            pub fn into_floating_input(self) -> [<Gpio $Port TypedPin>]<N, Input<Floating>> {
+                self.set_mode(PinMode::Input);
+                self.set_pull(Pull::Floating);
+                self.into_mode()
+            }
+
+            /// Convert to an input with a pull-up resistor.
+            pub fn into_pull_up_input(self) -> [<Gpio $Port TypedPin>]<N, Input<PullUp>> {
+                self.set_mode(PinMode::Input);
+                self.set_pull(Pull::Up);
+                self.into_mode()
+            }
+
+            /// Convert to an input with a pull-down resistor.
+            pub fn into_pull_down_input(self) -> [<Gpio $Port TypedPin>]<N, Input<PullDown>> {
+                self.set_mode(PinMode::Input);
+                self.set_pull(Pull::Dn);
+                self.into_mode()
+            }
+
+            /// Convert to a push-pull output.
+            pub fn into_push_pull_output(self) -> [<Gpio $Port TypedPin>]<N, Output<PushPull>> {
+                self.set_output_type(OutputType::PushPull);
+                self.set_mode(PinMode::Output);
+                self.into_mode()
+            }
+
+            /// Convert to an open-drain output.
+            pub fn into_open_drain_output(self) -> [<Gpio $Port TypedPin>]<N, Output<OpenDrain>> {
+                self.set_output_type(OutputType::OpenDrain);
+                self.set_mode(PinMode::Output);
+                self.into_mode()
+            }
+
+            /// Convert to an alternate function, eg `into_alternate::<7>()` for AF7. The output
+            /// type is left unchanged.
+            pub fn into_alternate<const AF: u8>(self) -> [<Gpio $Port TypedPin>]<N, Alternate<AF>> {
+                let regs = unsafe { &(*pac::[<GPIO $Port>]::ptr()) };
+                cortex_m::interrupt::free(|_| {
+                    cfg_if! {
+                        if #[cfg(any(feature = "l5", feature = "g0"))] {
+                            set_alt!(Self::PIN, regs, afsel, AF, [(0, l), (1, l), (2, l),
+                                (3, l), (4, l), (5, l), (6, l), (7, l), (8, h), (9, h), (10, h), (11, h), (12, h),
+                                (13, h), (14, h), (15, h)])
+                        } else {
+                            set_alt!(Self::PIN, regs, afr, AF, [(0, l), (1, l), (2, l),
+                                (3, l), (4, l), (5, l), (6, l), (7, l), (8, h), (9, h), (10, h), (11, h), (12, h),
+                                (13, h), (14, h), (15, h)])
+                        }
+                    }
+                });
+                self.into_mode()
+            }
+
+            /// Convert to an open-drain alternate function, eg for I2C.
+            pub fn into_alternate_open_drain<const AF: u8>(self) -> [<Gpio $Port TypedPin>]<N, Alternate<AF>> {
+                self.set_output_type(OutputType::OpenDrain);
+                self.into_alternate()
+            }
+
+            /// Convert to analog mode, eg for use with the ADC or DAC.
+            pub fn into_analog(self) -> [<Gpio $Port TypedPin>]<N, Analog> {
+                self.set_mode(PinMode::Analog);
+                self.into_mode()
+            }
+
+            /// Set output speed.
+            pub fn output_speed(&mut self, value: OutputSpeed) {
+                let regs = unsafe { &(*pac::[<GPIO $Port>]::ptr()) };
+                cortex_m::interrupt::free(|_| {
+                    set_field!(Self::PIN, regs, ospeedr, ospeedr, bits, value as u8, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+                });
+            }
+
+            /// Convert to a pin with a mode set at runtime, using the dynamic API.
+            pub fn into_dynamic(self) -> [<Gpio $Port Pin>] {
+                [<Gpio $Port Pin>] {
+                    port: PortLetter::[<$Port>],
+                    pin: Self::PIN,
+                }
+            }
+
+            /// Convert to a type-erased pin, eg to store pins from different ports together.
+            pub fn into_erased(self) -> Pin {
+                self.into_dynamic().into()
+            }
+
+            fn into_mode<NEW>(self) -> [<Gpio $Port TypedPin>]<N, NEW> {
+                [<Gpio $Port TypedPin>] {
+                    _mode: PhantomData,
+                }
+            }
+
+            // These modify registers shared with other pins on the port, so we don't allow
+            // interrupts during the read-modify-write.
+            fn set_mode(&self, value: PinMode) {
+                let regs = unsafe { &(*pac::[<GPIO $Port>]::ptr()) };
+                cortex_m::interrupt::free(|_| {
+                    set_field!(Self::PIN, regs, moder, moder, bits, value.val(), [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+                });
+            }
+
+            fn set_pull(&self, value: Pull) {
+                let regs = unsafe { &(*pac::[<GPIO $Port>]::ptr()) };
+                cortex_m::interrupt::free(|_| {
+                    set_field!(Self::PIN, regs, pupdr, pupdr, bits, value as u8, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+                });
+            }
+
+            fn set_output_type(&self, value: OutputType) {
+                let regs = unsafe { &(*pac::[<GPIO $Port>]::ptr()) };
+                cortex_m::interrupt::free(|_| {
+                    set_field!(Self::PIN, regs, otyper, ot, bit, value as u8 != 0, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+                });
+            }
+
+            fn read_idr(&self) -> bool {
+                unsafe { (*pac::[<GPIO $Port>]::ptr()).idr.read().bits() & (1 << N) != 0 }
+            }
+
+            fn read_odr(&self) -> bool {
+                unsafe { (*pac::[<GPIO $Port>]::ptr()).odr.read().bits() & (1 << N) != 0 }
+            }
+
+            fn write_bsrr(&self, high: bool) {
+                let offset = if high { 0 } else { 16 };
+                unsafe {
+                    (*pac::[<GPIO $Port>]::ptr()).bsrr.write(|w| w.bits(1 << (offset + N)))
+                }
+            }
+        }
+
+        impl<const N: u8, PULL> InputPin for [<Gpio $Port TypedPin>]<N, Input<PULL>> {
+            type Error = Infallible;
+
+            fn is_high(&self) -> Result<bool, Self::Error> {
+                Ok(self.read_idr())
+            }
+
+            fn is_low(&self) -> Result<bool, Self::Error> {
+                Ok(!self.read_idr())
+            }
+        }
+
+        // An open-drain output can be read, eg for bidirectional lines like 1-Wire.
+        impl<const N: u8> InputPin for [<Gpio $Port TypedPin>]<N, Output<OpenDrain>> {
+            type Error = Infallible;
+
+            fn is_high(&self) -> Result<bool, Self::Error> {
+                Ok(self.read_idr())
+            }
+
+            fn is_low(&self) -> Result<bool, Self::Error> {
+                Ok(!self.read_idr())
+            }
+        }
+
+        impl<const N: u8, TYPE> OutputPin for [<Gpio $Port TypedPin>]<N, Output<TYPE>> {
+            type Error = Infallible;
+
+            fn set_low(&mut self) -> Result<(), Self::Error> {
+                self.write_bsrr(false);
+                Ok(())
+            }
+
+            fn set_high(&mut self) -> Result<(), Self::Error> {
+                self.write_bsrr(true);
+                Ok(())
+            }
+        }
+
+        impl<const N: u8, TYPE> StatefulOutputPin for [<Gpio $Port TypedPin>]<N, Output<TYPE>> {
+            fn is_set_high(&self) -> Result<bool, Self::Error> {
+                Ok(self.read_odr())
+            }
+
+            fn is_set_low(&self) -> Result<bool, Self::Error> {
+                Ok(!self.read_odr())
+            }
+        }
+
+        impl<const N: u8, TYPE> ToggleableOutputPin for [<Gpio $Port TypedPin>]<N, Output<TYPE>> {
+            type Error = Infallible;
+
+            fn toggle(&mut self) -> Result<(), Self::Error> {
+                self.write_bsrr(!self.read_odr());
+                Ok(())
+            }
+        }
+        }
+
+        make_pin_aliases!($Port, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
     };
 }
 
@@ -768,3 +1433,247 @@ cfg_if! {
         make_pin!(H);
     }
 }
+
+/// Run `$body` with `$regs` bound to the register block of `$port`. Each port's register block
+/// is a different type, so the body is expanded once per port.
+macro_rules! with_port_regs {
+    ($port:expr, $regs:ident, $body:expr) => {
+        match $port {
+            PortLetter::A => {
+                let $regs = unsafe { &(*pac::GPIOA::ptr()) };
+                $body
+            }
+            PortLetter::B => {
+                let $regs = unsafe { &(*pac::GPIOB::ptr()) };
+                $body
+            }
+            PortLetter::C => {
+                let $regs = unsafe { &(*pac::GPIOC::ptr()) };
+                $body
+            }
+            #[cfg(not(any(feature = "f410")))]
+            PortLetter::D => {
+                let $regs = unsafe { &(*pac::GPIOD::ptr()) };
+                $body
+            }
+            #[cfg(not(any(feature = "f301", feature = "f3x4", feature = "f410", feature = "g0")))]
+            PortLetter::E => {
+                let $regs = unsafe { &(*pac::GPIOE::ptr()) };
+                $body
+            }
+            #[cfg(not(any(
+                feature = "f401",
+                feature = "f410",
+                feature = "f411",
+                feature = "l4x1",
+                feature = "l4x2",
+                feature = "l4x3"
+            )))]
+            PortLetter::F => {
+                let $regs = unsafe { &(*pac::GPIOF::ptr()) };
+                $body
+            }
+            #[cfg(not(any(
+                feature = "f373",
+                feature = "f301",
+                feature = "f3x4",
+                feature = "f410",
+                feature = "l4",
+                feature = "g0",
+                feature = "g4"
+            )))]
+            PortLetter::H => {
+                let $regs = unsafe { &(*pac::GPIOH::ptr()) };
+                $body
+            }
+            // `Pin` is only created from port-specific pins, so its port always exists.
+            _ => unreachable!(),
+        }
+    };
+}
+
+/// A type-erased GPIO pin, which stores its port at runtime. This lets pins from different
+/// ports be stored together, eg in an array for a keypad matrix, or a list of chip selects.
+/// Create one from a port-specific pin using `into()`, or `into_erased()` on a typed pin.
+/// Registers are accessed directly, so methods don't require the port's register block.
+#[derive(Clone, Copy)]
+pub struct Pin {
+    port: PortLetter,
+    pin: PinNum,
+}
+
+impl Pin {
+    /// Return the pin's port.
+    pub fn port(&self) -> PortLetter {
+        self.port
+    }
+
+    /// Return the pin number.
+    pub fn pin(&self) -> PinNum {
+        self.pin
+    }
+
+    /// Set pin mode.
+    pub fn mode(&mut self, value: PinMode) {
+        let pin = self.pin;
+        with_port_regs!(self.port, regs, {
+            cortex_m::interrupt::free(|_| {
+                if let PinMode::Alt(alt) = value {
+                    // This sets MODER as well.
+                    cfg_if! {
+                        if #[cfg(any(feature = "l5", feature = "g0"))] {
+                            set_alt!(pin, regs, afsel, alt, [(0, l), (1, l), (2, l),
+                                (3, l), (4, l), (5, l), (6, l), (7, l), (8, h), (9, h), (10, h), (11, h), (12, h),
+                                (13, h), (14, h), (15, h)])
+                        } else {
+                            set_alt!(pin, regs, afr, alt, [(0, l), (1, l), (2, l),
+                                (3, l), (4, l), (5, l), (6, l), (7, l), (8, h), (9, h), (10, h), (11, h), (12, h),
+                                (13, h), (14, h), (15, h)])
+                        }
+                    }
+                } else {
+                    set_field!(
+                        pin,
+                        regs,
+                        moder,
+                        moder,
+                        bits,
+                        value.val(),
+                        [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]
+                    );
+                }
+            })
+        })
+    }
+
+    /// Set output type.
+    pub fn output_type(&mut self, value: OutputType) {
+        let pin = self.pin;
+        with_port_regs!(self.port, regs, {
+            cortex_m::interrupt::free(|_| {
+                set_field!(
+                    pin,
+                    regs,
+                    otyper,
+                    ot,
+                    bit,
+                    value as u8 != 0,
+                    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]
+                );
+            })
+        })
+    }
+
+    /// Set internal pull resistor: Pull up, pull down, or floating.
+    pub fn pull(&mut self, value: Pull) {
+        let pin = self.pin;
+        with_port_regs!(self.port, regs, {
+            cortex_m::interrupt::free(|_| {
+                set_field!(
+                    pin,
+                    regs,
+                    pupdr,
+                    pupdr,
+                    bits,
+                    value as u8,
+                    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]
+                );
+            })
+        })
+    }
+
+    /// Check if this pin's EXTI line has an interrupt pending.
+    pub fn is_interrupt_pending(&self, exti: &EXTI) -> bool {
+        exti_pending(exti) & (1 << self.pin as u8) != 0
+    }
+
+    /// Clear this pin's EXTI line pending flag.
+    pub fn clear_interrupt(&mut self, exti: &mut EXTI) {
+        exti_clear_pending(exti, self.pin as u8);
+    }
+
+    /// Check if the pin's input voltage is high (VCC).
+    pub fn is_high(&self) -> bool {
+        with_port_regs!(self.port, regs, regs.idr.read().bits()) & (1 << self.pin as u8) != 0
+    }
+
+    /// Check if the pin's input voltage is low (ground).
+    pub fn is_low(&self) -> bool {
+        !self.is_high()
+    }
+
+    /// Set the pin's output voltage to high (VCC).
+    pub fn set_high(&mut self) {
+        self.write_bsrr(true);
+    }
+
+    /// Set the pin's output voltage to ground.
+    pub fn set_low(&mut self) {
+        self.write_bsrr(false);
+    }
+
+    /// Toggle the pin's output voltage.
+    pub fn toggle(&mut self) {
+        self.write_bsrr(!self.is_set_high());
+    }
+
+    /// Check if the pin's output data register is set high.
+    fn is_set_high(&self) -> bool {
+        with_port_regs!(self.port, regs, regs.odr.read().bits()) & (1 << self.pin as u8) != 0
+    }
+
+    fn write_bsrr(&self, high: bool) {
+        let offset = if high { 0 } else { 16 };
+        let bits = 1 << (offset + self.pin as u8);
+        with_port_regs!(
+            self.port,
+            regs,
+            regs.bsrr.write(|w| unsafe { w.bits(bits) })
+        )
+    }
+}
+
+impl InputPin for Pin {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(Pin::is_high(self))
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(Pin::is_low(self))
+    }
+}
+
+impl OutputPin for Pin {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Pin::set_low(self);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Pin::set_high(self);
+        Ok(())
+    }
+}
+
+impl StatefulOutputPin for Pin {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        Ok(Pin::is_set_high(self))
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        Ok(!Pin::is_set_high(self))
+    }
+}
+
+impl ToggleableOutputPin for Pin {
+    type Error = Infallible;
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        Pin::toggle(self);
+        Ok(())
+    }
+}
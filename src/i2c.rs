@@ -6,6 +6,7 @@ use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
 
 use crate::{
     pac::{i2c1, RCC},
+    pins::{SclPin, SdaPin},
     rcc_en_reset,
     traits::ClockCfg,
 };
@@ -148,6 +149,24 @@ where
         I2c { i2c }
     }
 
+    /// Like `new`, but takes the SCL and SDA pins, so a pin or alternate function that
+    /// can't be used with this I2C fails to compile.
+    pub fn new_with_pins<SCL, SDA, C>(
+        i2c: I2C,
+        device: I2cDevice,
+        _pins: (SCL, SDA),
+        freq: u32,
+        clocks: &C,
+        rcc: &mut RCC,
+    ) -> Self
+    where
+        SCL: SclPin<I2C>,
+        SDA: SdaPin<I2C>,
+        C: ClockCfg,
+    {
+        Self::new(i2c, device, freq, clocks, rcc)
+    }
+
     /// Frees the I2C peripheral
     pub fn free(self) -> I2C {
         self.i2c
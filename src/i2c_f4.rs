@@ -8,6 +8,7 @@ use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
 
 use crate::{
     pac::{self, i2c1, RCC},
+    pins::{SclPin, SdaPin},
     traits::ClockCfg,
 };
 
@@ -87,6 +88,23 @@ where {
         i2c
     }
 
+    /// Like `new`, but takes the SCL and SDA pins, so a pin or alternate function that
+    /// can't be used with this I2C fails to compile.
+    pub fn new_with_pins<SCL, SDA, C>(
+        i2c: I2C,
+        _pins: (SCL, SDA),
+        speed: u32,
+        clocks: &C,
+        rcc: &mut RCC,
+    ) -> Self
+    where
+        SCL: SclPin<I2C>,
+        SDA: SdaPin<I2C>,
+        C: ClockCfg,
+    {
+        Self::new(i2c, speed, clocks, rcc)
+    }
+
     fn i2c_init(&self, speed: u32, pclk: u32) {
         // Make sure the I2C unit is disabled so we can configure it
         self.i2c.cr1.modify(|_, w| w.pe().clear_bit());
@@ -216,7 +216,16 @@ pub mod traits;
 
 #[cfg(not(any(feature = "f301", feature = "f302")))]
 pub mod adc;
+pub mod button;
 pub mod clocks;
+#[cfg(not(any(
+    feature = "f4",
+    feature = "g030",
+    feature = "g031",
+    feature = "g041",
+    feature = "g070"
+)))]
+pub mod comp;
 #[cfg(not(any(feature = "f4", feature = "g0", feature = "g4", feature = "l5")))] // todo
 pub mod crc;
 pub mod dac;
@@ -235,6 +244,7 @@ pub mod i2c_f4;
 pub use i2c_f4 as i2c;
 
 pub mod low_power;
+pub mod pins;
 
 #[cfg(not(any(
     feature = "f3",
@@ -250,6 +260,7 @@ pub mod low_power;
     feature = "h7b3",
 )))]
 pub mod qspi;
+pub mod reset;
 pub mod rtc;
 
 // #[cfg(not(feature = "f4"))]
@@ -262,6 +273,7 @@ pub mod usart;
 pub mod spi;
 
 pub mod timer;
+pub mod watchdog;
 
 // In the l4 series, only l4x2 and l4x3 have USB.
 cfg_if::cfg_if! {
@@ -0,0 +1,211 @@
+//! Compile-time pin-to-peripheral mappings. Marker traits, eg `TxPin<USART1>`, are implemented
+//! for typed GPIO pins (eg `PA9<Alternate<7>>`) that can be used with a peripheral, from the
+//! alternate function tables in each family's datasheet. Peripheral constructors that take
+//! pins (eg `Usart::new_with_pins`) use these as bounds, so a wrong pin or AF fails to compile.
+//!
+//! These tables cover common pins, and aren't exhaustive; if a valid pin isn't listed,
+//! use the constructor that doesn't take pins, or implement the trait for it.
+
+use crate::{gpio::*, pac};
+
+use cfg_if::cfg_if;
+
+/// A pin that can be used as a USART's TX line.
+pub trait TxPin<U> {}
+
+/// A pin that can be used as a USART's RX line.
+pub trait RxPin<U> {}
+
+/// A pin that can be used as an SPI's SCK line.
+pub trait SckPin<S> {}
+
+/// A pin that can be used as an SPI's MISO line.
+pub trait MisoPin<S> {}
+
+/// A pin that can be used as an SPI's MOSI line.
+pub trait MosiPin<S> {}
+
+/// A pin that can be used as an I2C's SCL line. Configure it as open drain, eg with
+/// `into_alternate_open_drain`.
+pub trait SclPin<I> {}
+
+/// A pin that can be used as an I2C's SDA line. Configure it as open drain, eg with
+/// `into_alternate_open_drain`.
+pub trait SdaPin<I> {}
+
+/// A pin that can be used as the QSPI clock line.
+pub trait QspiClkPin<Q> {}
+
+/// A pin that can be used as the QSPI bank 1 chip select line.
+pub trait QspiNcsPin<Q> {}
+
+/// A pin that can be used as QSPI bank 1 IO0.
+pub trait QspiIo0Pin<Q> {}
+
+/// A pin that can be used as QSPI bank 1 IO1.
+pub trait QspiIo1Pin<Q> {}
+
+/// A pin that can be used as QSPI bank 1 IO2.
+pub trait QspiIo2Pin<Q> {}
+
+/// A pin that can be used as QSPI bank 1 IO3.
+pub trait QspiIo3Pin<Q> {}
+
+/// Implement a pin trait for a peripheral, for each (pin, AF) pair.
+macro_rules! pins {
+    ($Trait:ident, $PERIPH:ident, [$(($Pin:ident, $af:literal)),+]) => {
+        $(
+            impl $Trait<pac::$PERIPH> for $Pin<Alternate<$af>> {}
+        )+
+    };
+}
+
+// USART pins. (Datasheets: Alternate function tables)
+cfg_if! {
+    if #[cfg(feature = "f3")] {
+        pins!(TxPin, USART1, [(PA9, 7), (PB6, 7)]);
+        pins!(RxPin, USART1, [(PA10, 7), (PB7, 7)]);
+        pins!(TxPin, USART2, [(PA2, 7), (PA14, 7), (PB3, 7)]);
+        pins!(RxPin, USART2, [(PA3, 7), (PA15, 7), (PB4, 7)]);
+        pins!(TxPin, USART3, [(PB10, 7)]);
+        pins!(RxPin, USART3, [(PB11, 7)]);
+    } else if #[cfg(feature = "f4")] {
+        pins!(TxPin, USART1, [(PA9, 7), (PB6, 7)]);
+        pins!(RxPin, USART1, [(PA10, 7), (PB7, 7)]);
+        pins!(TxPin, USART2, [(PA2, 7)]);
+        pins!(RxPin, USART2, [(PA3, 7)]);
+    } else if #[cfg(feature = "l4")] {
+        pins!(TxPin, USART1, [(PA9, 7), (PB6, 7)]);
+        pins!(RxPin, USART1, [(PA10, 7), (PB7, 7)]);
+        pins!(TxPin, USART2, [(PA2, 7)]);
+        pins!(RxPin, USART2, [(PA3, 7)]);
+        pins!(TxPin, USART3, [(PB10, 7), (PC4, 7), (PC10, 7)]);
+        pins!(RxPin, USART3, [(PB11, 7), (PC5, 7), (PC11, 7)]);
+    } else if #[cfg(feature = "l5")] {
+        pins!(TxPin, USART1, [(PA9, 7), (PB6, 7)]);
+        pins!(RxPin, USART1, [(PA10, 7), (PB7, 7)]);
+        pins!(TxPin, USART2, [(PA2, 7), (PD5, 7)]);
+        pins!(RxPin, USART2, [(PA3, 7), (PD6, 7)]);
+        pins!(TxPin, USART3, [(PB10, 7), (PC4, 7), (PC10, 7)]);
+        pins!(RxPin, USART3, [(PB11, 7), (PC5, 7), (PC11, 7)]);
+    } else if #[cfg(feature = "g0")] {
+        pins!(TxPin, USART1, [(PA9, 1), (PB6, 0), (PC4, 1)]);
+        pins!(RxPin, USART1, [(PA10, 1), (PB7, 0), (PC5, 1)]);
+        pins!(TxPin, USART2, [(PA2, 1), (PA14, 1)]);
+        pins!(RxPin, USART2, [(PA3, 1), (PA15, 1)]);
+    } else if #[cfg(feature = "g4")] {
+        pins!(TxPin, USART1, [(PA9, 7), (PB6, 7), (PC4, 7)]);
+        pins!(RxPin, USART1, [(PA10, 7), (PB7, 7), (PC5, 7)]);
+        pins!(TxPin, USART2, [(PA2, 7), (PA14, 7), (PB3, 7)]);
+        pins!(RxPin, USART2, [(PA3, 7), (PA15, 7), (PB4, 7)]);
+        pins!(TxPin, USART3, [(PB9, 7), (PB10, 7), (PC10, 7)]);
+        pins!(RxPin, USART3, [(PB8, 7), (PB11, 7), (PC11, 7)]);
+    } else if #[cfg(feature = "h7")] {
+        pins!(TxPin, USART1, [(PA9, 7), (PB6, 7), (PB14, 4)]);
+        pins!(RxPin, USART1, [(PA10, 7), (PB7, 7), (PB15, 4)]);
+        pins!(TxPin, USART2, [(PA2, 7), (PD5, 7)]);
+        pins!(RxPin, USART2, [(PA3, 7), (PD6, 7)]);
+        pins!(TxPin, USART3, [(PB10, 7), (PC10, 7), (PD8, 7)]);
+        pins!(RxPin, USART3, [(PB11, 7), (PC11, 7), (PD9, 7)]);
+    }
+}
+
+// SPI pins. (No H7 SPI module yet)
+cfg_if! {
+    if #[cfg(feature = "f3")] {
+        pins!(SckPin, SPI1, [(PA5, 5)]);
+        pins!(MisoPin, SPI1, [(PA6, 5)]);
+        pins!(MosiPin, SPI1, [(PA7, 5)]);
+        pins!(SckPin, SPI2, [(PB13, 5)]);
+        pins!(MisoPin, SPI2, [(PB14, 5)]);
+        pins!(MosiPin, SPI2, [(PB15, 5)]);
+        pins!(SckPin, SPI3, [(PB3, 6), (PC10, 6)]);
+        pins!(MisoPin, SPI3, [(PB4, 6), (PC11, 6)]);
+        pins!(MosiPin, SPI3, [(PB5, 6), (PC12, 6)]);
+    } else if #[cfg(feature = "f4")] {
+        pins!(SckPin, SPI1, [(PA5, 5), (PB3, 5)]);
+        pins!(MisoPin, SPI1, [(PA6, 5), (PB4, 5)]);
+        pins!(MosiPin, SPI1, [(PA7, 5), (PB5, 5)]);
+        pins!(SckPin, SPI2, [(PB10, 5), (PB13, 5)]);
+        pins!(MisoPin, SPI2, [(PB14, 5), (PC2, 5)]);
+        pins!(MosiPin, SPI2, [(PB15, 5), (PC3, 5)]);
+        #[cfg(not(feature = "f410"))]
+        pins!(SckPin, SPI3, [(PB3, 6), (PC10, 6)]);
+        #[cfg(not(feature = "f410"))]
+        pins!(MisoPin, SPI3, [(PB4, 6), (PC11, 6)]);
+        #[cfg(not(feature = "f410"))]
+        pins!(MosiPin, SPI3, [(PB5, 6), (PC12, 6)]);
+    } else if #[cfg(any(feature = "l4", feature = "l5", feature = "g4"))] {
+        pins!(SckPin, SPI1, [(PA5, 5), (PB3, 5)]);
+        pins!(MisoPin, SPI1, [(PA6, 5), (PB4, 5)]);
+        pins!(MosiPin, SPI1, [(PA7, 5), (PB5, 5)]);
+        pins!(SckPin, SPI2, [(PB13, 5)]);
+        pins!(MisoPin, SPI2, [(PB14, 5)]);
+        pins!(MosiPin, SPI2, [(PB15, 5)]);
+        pins!(SckPin, SPI3, [(PB3, 6), (PC10, 6)]);
+        pins!(MisoPin, SPI3, [(PB4, 6), (PC11, 6)]);
+        pins!(MosiPin, SPI3, [(PB5, 6), (PC12, 6)]);
+    } else if #[cfg(feature = "g0")] {
+        pins!(SckPin, SPI1, [(PA1, 0), (PA5, 0), (PB3, 0)]);
+        pins!(MisoPin, SPI1, [(PA6, 0), (PA11, 0), (PB4, 0)]);
+        pins!(MosiPin, SPI1, [(PA2, 0), (PA7, 0), (PA12, 0), (PB5, 0)]);
+        pins!(SckPin, SPI2, [(PB13, 0)]);
+        pins!(MisoPin, SPI2, [(PB14, 0)]);
+        pins!(MosiPin, SPI2, [(PB15, 0)]);
+    }
+}
+
+// I2C pins
+cfg_if! {
+    if #[cfg(feature = "f3")] {
+        pins!(SclPin, I2C1, [(PA15, 4), (PB6, 4), (PB8, 4)]);
+        pins!(SdaPin, I2C1, [(PA14, 4), (PB7, 4), (PB9, 4)]);
+        pins!(SclPin, I2C2, [(PA9, 4)]);
+        pins!(SdaPin, I2C2, [(PA10, 4)]);
+    } else if #[cfg(feature = "f4")] {
+        pins!(SclPin, I2C1, [(PB6, 4), (PB8, 4)]);
+        pins!(SdaPin, I2C1, [(PB7, 4), (PB9, 4)]);
+    } else if #[cfg(any(feature = "l4", feature = "l5"))] {
+        pins!(SclPin, I2C1, [(PB6, 4), (PB8, 4)]);
+        pins!(SdaPin, I2C1, [(PB7, 4), (PB9, 4)]);
+        pins!(SclPin, I2C2, [(PB10, 4), (PB13, 4)]);
+        pins!(SdaPin, I2C2, [(PB11, 4), (PB14, 4)]);
+    } else if #[cfg(feature = "g0")] {
+        pins!(SclPin, I2C1, [(PA9, 6), (PB6, 6), (PB8, 6)]);
+        pins!(SdaPin, I2C1, [(PA10, 6), (PB7, 6), (PB9, 6)]);
+        pins!(SclPin, I2C2, [(PA11, 6), (PB10, 6), (PB13, 6)]);
+        pins!(SdaPin, I2C2, [(PA12, 6), (PB11, 6), (PB14, 6)]);
+    } else if #[cfg(feature = "g4")] {
+        pins!(SclPin, I2C1, [(PA13, 4), (PA15, 4), (PB8, 4)]);
+        pins!(SdaPin, I2C1, [(PA14, 4), (PB7, 4), (PB9, 4)]);
+        pins!(SclPin, I2C2, [(PA9, 4), (PC4, 4)]);
+        pins!(SdaPin, I2C2, [(PA8, 4)]);
+    } else if #[cfg(feature = "h7")] {
+        pins!(SclPin, I2C1, [(PB6, 4), (PB8, 4)]);
+        pins!(SdaPin, I2C1, [(PB7, 4), (PB9, 4)]);
+        pins!(SclPin, I2C2, [(PB10, 4), (PF1, 4)]);
+        pins!(SdaPin, I2C2, [(PB11, 4), (PF0, 4)]);
+        pins!(SclPin, I2C3, [(PA8, 4)]);
+        pins!(SdaPin, I2C3, [(PC9, 4)]);
+    }
+}
+
+// QSPI bank 1 pins. Families match the `qspi` module.
+cfg_if! {
+    if #[cfg(any(feature = "l4x1", feature = "l4x2", feature = "l4x5", feature = "l4x6",
+        feature = "g473", feature = "g474", feature = "g483", feature = "g484"))] {
+        pins!(QspiClkPin, QUADSPI, [(PA3, 10), (PB10, 10)]);
+        pins!(QspiNcsPin, QUADSPI, [(PA2, 10), (PB11, 10)]);
+        pins!(QspiIo0Pin, QUADSPI, [(PB1, 10)]);
+        pins!(QspiIo1Pin, QUADSPI, [(PB0, 10)]);
+        pins!(QspiIo2Pin, QUADSPI, [(PA7, 10)]);
+        pins!(QspiIo3Pin, QUADSPI, [(PA6, 10)]);
+    } else if #[cfg(all(feature = "h7", not(feature = "h7b3")))] {
+        pins!(QspiClkPin, QUADSPI, [(PB2, 9), (PF10, 9)]);
+        pins!(QspiNcsPin, QUADSPI, [(PB6, 10), (PB10, 9)]);
+        pins!(QspiIo0Pin, QUADSPI, [(PC9, 9), (PD11, 9), (PF8, 10)]);
+        pins!(QspiIo1Pin, QUADSPI, [(PC10, 9), (PD12, 9), (PF9, 10)]);
+        pins!(QspiIo2Pin, QUADSPI, [(PE2, 9), (PF7, 9)]);
+        pins!(QspiIo3Pin, QUADSPI, [(PA1, 9), (PD13, 9), (PF6, 9)]);
+    }
+}
@@ -24,6 +24,7 @@
 
 use crate::{
     pac::{self, QUADSPI, RCC},
+    pins::{QspiClkPin, QspiIo0Pin, QspiIo1Pin, QspiIo2Pin, QspiIo3Pin, QspiNcsPin},
     traits::ClockCfg,
 };
 
@@ -235,6 +236,28 @@ impl Qspi {
         Self::qspi_unchecked(regs, config, Bank::One, clocks, rcc)
     }
 
+    /// Like `bank1`, but takes the CLK, NCS, and IO0-3 pins, so a pin or alternate function
+    /// that can't be used with QSPI bank 1 fails to compile.
+    pub fn bank1_with_pins<CLK, NCS, IO0, IO1, IO2, IO3, CONFIG, C>(
+        regs: QUADSPI,
+        _pins: (CLK, NCS, IO0, IO1, IO2, IO3),
+        config: CONFIG,
+        clocks: &C,
+        rcc: &mut RCC,
+    ) -> Self
+    where
+        CLK: QspiClkPin<QUADSPI>,
+        NCS: QspiNcsPin<QUADSPI>,
+        IO0: QspiIo0Pin<QUADSPI>,
+        IO1: QspiIo1Pin<QUADSPI>,
+        IO2: QspiIo2Pin<QUADSPI>,
+        IO3: QspiIo3Pin<QUADSPI>,
+        CONFIG: Into<Config>,
+        C: ClockCfg,
+    {
+        Self::bank1(regs, config, clocks, rcc)
+    }
+
     pub fn bank2<CONFIG, C>(regs: QUADSPI, config: CONFIG, clocks: &C, rcc: &mut RCC) -> Self
     where
         CONFIG: Into<Config>,
@@ -0,0 +1,154 @@
+//! Reset cause detection, using the reset flags in `RCC_CSR` (`RCC_RSR` on H7). The flags
+//! persist across resets (except power-on resets), so clear them after reading, or the next
+//! reset will report a stale cause.
+
+use crate::pac::RCC;
+
+use cfg_if::cfg_if;
+
+/// The cause of the most recent reset.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResetReason {
+    /// Power-on or power-down reset (POR/PDR).
+    PowerOn,
+    /// Brownout reset. On L4, L5, G0 and G4, this is also reported on power-on, since
+    /// these families don't have a separate POR flag.
+    Brownout,
+    /// Reset from the NRST pin.
+    Pin,
+    /// Software reset, eg from `SCB::sys_reset()`.
+    Software,
+    /// Independent watchdog reset.
+    IndependentWatchdog,
+    /// Window watchdog reset.
+    WindowWatchdog,
+    /// Low-power reset, ie entering Stop or Standby with `nRST_STOP` or `nRST_STDBY`
+    /// option bytes cleared.
+    LowPower,
+    /// Reset from an option byte load (`OBL_LAUNCH`).
+    OptionByteLoad,
+    /// Firewall reset.
+    Firewall,
+    /// No reset flags are set, eg if they've already been cleared.
+    Unknown,
+}
+
+/// Read the reset flags, and return the cause of the most recent reset. If multiple flags
+/// are set, the most specific one is reported: Internal causes are reported over the pin flag,
+/// since internal resets also drive NRST low.
+pub fn reset_reason(rcc: &RCC) -> ResetReason {
+    cfg_if! {
+        if #[cfg(feature = "h7")] {
+            let csr = rcc.c1_rsr.read();
+        } else {
+            let csr = rcc.csr.read();
+        }
+    }
+
+    cfg_if! {
+        if #[cfg(feature = "l4")] {
+            if csr.firewallrstf().bit_is_set() {
+                return ResetReason::Firewall;
+            }
+        }
+    }
+
+    #[cfg(not(any(feature = "f4", feature = "h7")))]
+    if csr.oblrstf().bit_is_set() {
+        return ResetReason::OptionByteLoad;
+    }
+
+    cfg_if! {
+        if #[cfg(feature = "f4")] {
+            // F4 names the IWDG reset flag `WDGRSTF`.
+            if csr.wdgrstf().bit_is_set() {
+                return ResetReason::IndependentWatchdog;
+            }
+        } else if #[cfg(feature = "l5")] {
+            if csr.iwwdgrstf().bit_is_set() {
+                return ResetReason::IndependentWatchdog;
+            }
+        } else if #[cfg(feature = "h7")] {
+            if csr.iwdg1rstf().bit_is_set() {
+                return ResetReason::IndependentWatchdog;
+            }
+        } else {
+            if csr.iwdgrstf().bit_is_set() {
+                return ResetReason::IndependentWatchdog;
+            }
+        }
+    }
+
+    cfg_if! {
+        if #[cfg(feature = "h7")] {
+            if csr.wwdg1rstf().bit_is_set() {
+                return ResetReason::WindowWatchdog;
+            }
+        } else {
+            if csr.wwdgrstf().bit_is_set() {
+                return ResetReason::WindowWatchdog;
+            }
+        }
+    }
+
+    if csr.sftrstf().bit_is_set() {
+        return ResetReason::Software;
+    }
+
+    cfg_if! {
+        if #[cfg(any(feature = "l4", feature = "l5", feature = "g4"))] {
+            if csr.lpwrstf().bit_is_set() {
+                return ResetReason::LowPower;
+            }
+        } else {
+            if csr.lpwrrstf().bit_is_set() {
+                return ResetReason::LowPower;
+            }
+        }
+    }
+
+    // On families with both, a POR also sets the BOR flag, so check POR first.
+    #[cfg(any(feature = "f3", feature = "f4", feature = "h7"))]
+    if csr.porrstf().bit_is_set() {
+        return ResetReason::PowerOn;
+    }
+
+    cfg_if! {
+        if #[cfg(feature = "g0")] {
+            // G0's `PWRRSTF` is set on BOR or POR/PDR.
+            if csr.pwrrstf().bit_is_set() {
+                return ResetReason::Brownout;
+            }
+        } else if #[cfg(not(feature = "f3"))] {
+            if csr.borrstf().bit_is_set() {
+                return ResetReason::Brownout;
+            }
+        }
+    }
+
+    cfg_if! {
+        if #[cfg(feature = "f4")] {
+            if csr.padrstf().bit_is_set() {
+                return ResetReason::Pin;
+            }
+        } else {
+            if csr.pinrstf().bit_is_set() {
+                return ResetReason::Pin;
+            }
+        }
+    }
+
+    ResetReason::Unknown
+}
+
+/// Clear the reset flags, by setting `RMVF`. Call this after `reset_reason`, so the next
+/// reset reports its own cause.
+pub fn clear_reset_flags(rcc: &mut RCC) {
+    cfg_if! {
+        if #[cfg(feature = "h7")] {
+            rcc.c1_rsr.modify(|_, w| w.rmvf().set_bit());
+        } else {
+            rcc.csr.modify(|_, w| w.rmvf().set_bit());
+        }
+    }
+}
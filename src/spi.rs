@@ -10,6 +10,7 @@ use cfg_if::cfg_if;
 
 use crate::{
     pac::{self, RCC},
+    pins::{MisoPin, MosiPin, SckPin},
     rcc_en_reset,
     traits::ClockCfg,
 };
@@ -323,6 +324,26 @@ where
         Spi { regs }
     }
 
+    /// Like `new`, but takes the SCK, MISO, and MOSI pins, so a pin or alternate function
+    /// that can't be used with this SPI fails to compile.
+    pub fn new_with_pins<SCK, MISO, MOSI, C>(
+        regs: S,
+        device: SpiDevice,
+        _pins: (SCK, MISO, MOSI),
+        mode: Mode,
+        freq: u32,
+        clocks: &C,
+        rcc: &mut RCC,
+    ) -> Self
+    where
+        SCK: SckPin<S>,
+        MISO: MisoPin<S>,
+        MOSI: MosiPin<S>,
+        C: ClockCfg,
+    {
+        Self::new(regs, device, mode, freq, clocks, rcc)
+    }
+
     #[cfg(not(feature = "h7"))]
     /// Change the baud rate of the SPI
     pub fn reclock<F, C: ClockCfg>(&mut self, freq: u32, clocks: C) {
@@ -1,8 +1,13 @@
 //! Timers. Includes initialization, countdown functionality, interrupts, and PWM features.
 
+use core::marker::PhantomData;
+
 use num_traits::float::Float;
 
-use embedded_hal::timer::{CountDown, Periodic};
+use embedded_hal::{
+    blocking::delay::{DelayMs, DelayUs},
+    timer::{CountDown, Periodic},
+};
 
 use void::Void;
 
@@ -29,6 +34,51 @@ pub struct Timer<TIM> {
     tim: TIM,         // Register block for the specific timer.
 }
 
+/// A single PWM output channel, split from a `Timer` using its `split` method. Channels
+/// can be moved independently, eg to separate drivers or tasks. Duty changes are single
+/// CCR register writes; shared registers (eg CCER) are modified in a critical section.
+pub struct PwmChannel<TIM> {
+    channel: Channel,
+    _tim: PhantomData<TIM>,
+}
+
+/// The 4 PWM channels of a timer, as returned by `Timer::split`. This keeps the timer; get it
+/// back with `free`, eg to change its frequency.
+pub struct PwmChannels<TIM> {
+    pub c1: PwmChannel<TIM>,
+    pub c2: PwmChannel<TIM>,
+    pub c3: PwmChannel<TIM>,
+    pub c4: PwmChannel<TIM>,
+    timer: Timer<TIM>,
+}
+
+impl<TIM> PwmChannels<TIM> {
+    /// Release the timer, consuming the channel handles. The timer and its channels keep their
+    /// configuration, and keep running if enabled.
+    pub fn free(self) -> Timer<TIM> {
+        self.timer
+    }
+}
+
+impl<TIM> PwmChannel<TIM> {
+    /// Returns the channel this handle controls.
+    pub fn channel(&self) -> Channel {
+        self.channel
+    }
+}
+
+/// A monotonic clock that extends a hardware timer's counter into a 64-bit tick count, by
+/// counting overflows. Create it with `Monotonic::new`, and call `handle_interrupt` from the
+/// timer's interrupt handler. Channel 1's compare is used for the wake alarm, so this is available
+/// on all timers except the basic ones (eg TIM6 and TIM7).
+pub struct Monotonic<TIM> {
+    timer: Timer<TIM>,
+    tick_freq: u32,     // Hz
+    period: u64,        // Ticks per overflow; ARR + 1.
+    overflows: u64,     // Number of overflows since the clock was started.
+    alarm: Option<u64>, // Deadline in ticks for the compare interrupt, if set.
+}
+
 /// Interrupt events
 pub enum Event {
     /// Timer timed out / count down ended
@@ -173,6 +223,47 @@ impl OutputCompare {
     }
 }
 
+/// Timer DMA requests. See the `DIER` register's `UDE` and `CCxDE` bits.
+#[derive(Clone, Copy)]
+pub enum DmaRequest {
+    /// DMA request on update event
+    Update,
+    /// DMA request on a capture/compare event for a channel
+    CaptureCompare(Channel),
+}
+
+/// A single-wire protocol that encodes bits as PWM pulses of different widths, with
+/// one bit per timer period. Set the timer period to the protocol's bit rate
+/// (eg 800kHz for WS2812, 600kHz for DShot600) before encoding. For adequate duty
+/// resolution, use `set_prescaler(0)` and `set_auto_reload` instead of `set_freq`.
+#[derive(Clone, Copy)]
+pub enum WaveformProtocol {
+    /// WS2812 (NeoPixel) LEDs. T0H = 0.4µs, T1H = 0.8µs of a 1.25µs period.
+    Ws2812,
+    /// DShot ESC protocol. A 0 is high for 37.5% of the period; a 1 for 75%.
+    Dshot,
+}
+
+#[cfg(not(feature = "l5"))]
+impl WaveformProtocol {
+    /// High time of a 0 and a 1 bit respectively, as a portion of the period.
+    fn duty_portions(&self) -> (f32, f32) {
+        match self {
+            Self::Ws2812 => (0.32, 0.64),
+            Self::Dshot => (0.375, 0.75),
+        }
+    }
+}
+
+/// Build a 16-bit DShot frame from an 11-bit throttle value (0 - 2_047; 1 - 47 are
+/// reserved for commands) and the telemetry request bit. Returned as big-endian
+/// bytes, ready to pass to `encode_waveform`.
+pub fn dshot_frame(throttle: u16, telemetry: bool) -> [u8; 2] {
+    let packet = ((throttle & 0x7ff) << 1) | telemetry as u16;
+    let crc = (packet ^ (packet >> 4) ^ (packet >> 8)) & 0xf;
+    ((packet << 4) | crc).to_be_bytes()
+}
+
 macro_rules! hal {
     ($TIMX:ident, $tim:ident, $apb:expr) => {
         impl Periodic for Timer<pac::$TIMX> {}
@@ -210,6 +301,42 @@ macro_rules! hal {
             }
         }
 
+        impl DelayMs<u32> for Timer<pac::$TIMX> {
+            fn delay_ms(&mut self, ms: u32) {
+                Timer::<pac::$TIMX>::delay_ms(self, ms);
+            }
+        }
+
+        impl DelayMs<u16> for Timer<pac::$TIMX> {
+            fn delay_ms(&mut self, ms: u16) {
+                Timer::<pac::$TIMX>::delay_ms(self, ms as u32);
+            }
+        }
+
+        impl DelayMs<u8> for Timer<pac::$TIMX> {
+            fn delay_ms(&mut self, ms: u8) {
+                Timer::<pac::$TIMX>::delay_ms(self, ms as u32);
+            }
+        }
+
+        impl DelayUs<u32> for Timer<pac::$TIMX> {
+            fn delay_us(&mut self, us: u32) {
+                Timer::<pac::$TIMX>::delay_us(self, us);
+            }
+        }
+
+        impl DelayUs<u16> for Timer<pac::$TIMX> {
+            fn delay_us(&mut self, us: u16) {
+                Timer::<pac::$TIMX>::delay_us(self, us as u32);
+            }
+        }
+
+        impl DelayUs<u8> for Timer<pac::$TIMX> {
+            fn delay_us(&mut self, us: u8) {
+                Timer::<pac::$TIMX>::delay_us(self, us as u32);
+            }
+        }
+
         impl Timer<pac::$TIMX> {
             paste! {
                 /// Configures a TIM peripheral as a periodic count down timer
@@ -320,6 +447,58 @@ macro_rules! hal {
             pub fn reset_countdown(&mut self) {
                 self.tim.cnt.write(|w| unsafe { w.bits(0) });
             }
+
+            /// Block for a duration, in ms, using this timer as a delay provider.
+            /// See the note on `delay_us`.
+            pub fn delay_ms(&mut self, ms: u32) {
+                self.delay_ticks_us(ms as u64 * 1_000);
+            }
+
+            /// Block for a duration, in µs, using this timer as a delay provider. This changes
+            /// the timer's prescaler and auto-reload values, and leaves it disabled; reconfigure
+            /// it afterwards if using it for other purposes. With a timer clock below 1MHz, the
+            /// delay is rounded to whole timer ticks, and is at least one tick.
+            pub fn delay_us(&mut self, us: u32) {
+                self.delay_ticks_us(us as u64);
+            }
+
+            /// Delay for a duration in µs, counting timer ticks of about 1µs, or of one timer
+            /// clock period if the timer clock is slower than 1MHz.
+            fn delay_ticks_us(&mut self, us: u64) {
+                // Limit each run to the range of a 16-bit counter.
+                const MAX_TICKS: u64 = 0xffff;
+
+                if us == 0 {
+                    return;
+                }
+
+                let psc = (self.clock_speed / 1_000_000).max(1) - 1;
+
+                self.disable();
+                self.set_prescaler(psc as u16);
+
+                // Compute in u64, so long delays at high clock speeds don't overflow.
+                let tick_freq = self.clock_speed as u64 / (psc as u64 + 1);
+                let mut remaining = (tick_freq * us / 1_000_000).max(1);
+
+                while remaining != 0 {
+                    let ticks = remaining.min(MAX_TICKS);
+
+                    self.set_auto_reload(ticks as u32);
+                    // Load the prescaler and auto-reload values. This resets the counter.
+                    self.tim.egr.write(|w| w.ug().set_bit());
+                    self.clear_update_interrupt_flag();
+                    // Counting from 1 up to ARR, the update occurs after `ticks` ticks.
+                    self.tim.cnt.write(|w| unsafe { w.bits(1) });
+
+                    self.enable();
+                    while self.tim.sr.read().uif().bit_is_clear() {}
+                    self.disable();
+                    self.clear_update_interrupt_flag();
+
+                    remaining -= ticks;
+                }
+            }
         }
     }
 }
@@ -360,6 +539,175 @@ fn calc_freq_vals(freq: f32, clock_speed: u32) -> Result<(u16, u16), ValueError>
     Ok((psc, arr))
 }
 
+// A free-running monotonic clock, for timers with a channel 1 compare, which it uses for the
+// alarm. Basic timers (eg TIM6 and TIM7) don't have one.
+macro_rules! monotonic {
+    ($TIMX:ident) => {
+        impl Monotonic<pac::$TIMX> {
+            /// Start a free-running monotonic clock on a timer, with a tick frequency in Hz.
+            /// `tick_freq` must evenly divide the timer's clock speed, with a quotient of
+            /// 65_536 or less. This enables the timer's update interrupt; unmask it in the NVIC.
+            pub fn new(mut timer: Timer<pac::$TIMX>, tick_freq: u32) -> Result<Self, ValueError> {
+                if tick_freq == 0
+                    || timer.clock_speed % tick_freq != 0
+                    || timer.clock_speed / tick_freq > 65_536
+                {
+                    return Err(ValueError {});
+                }
+
+                timer.disable();
+                timer.set_prescaler((timer.clock_speed / tick_freq - 1) as u16);
+
+                // Use the full counter range. 16-bit timers ignore the upper half of this
+                // write, so read it back to find the resolution.
+                timer.set_auto_reload(0xffff_ffff);
+                let period = timer.tim.arr.read().bits() as u64 + 1;
+
+                // Load the prescaler, without raising an update interrupt.
+                timer.tim.cr1.modify(|_, w| w.urs().set_bit());
+                timer.tim.egr.write(|w| w.ug().set_bit());
+                timer.clear_update_interrupt_flag();
+                timer.reset_countdown();
+
+                timer.tim.dier.modify(|_, w| w.uie().set_bit());
+                timer.enable();
+
+                Ok(Self {
+                    timer,
+                    tick_freq,
+                    period,
+                    overflows: 0,
+                    alarm: None,
+                })
+            }
+
+            /// The current tick count.
+            pub fn now(&self) -> u64 {
+                cortex_m::interrupt::free(|_| {
+                    let mut overflows = self.overflows;
+                    let mut count = self.timer.tim.cnt.read().bits() as u64;
+
+                    // If an overflow is pending but not yet handled, the count may have been read
+                    // either side of it. Read it again, so it's after the overflow.
+                    if self.timer.tim.sr.read().uif().bit_is_set() {
+                        count = self.timer.tim.cnt.read().bits() as u64;
+                        overflows += 1;
+                    }
+
+                    overflows * self.period + count
+                })
+            }
+
+            /// The time since the clock was started, in µs.
+            pub fn now_us(&self) -> u64 {
+                self.ticks_to_us(self.now())
+            }
+
+            /// Convert a number of ticks to µs.
+            pub fn ticks_to_us(&self, ticks: u64) -> u64 {
+                let freq = self.tick_freq as u64;
+                // Split the division, to avoid overflowing for large tick counts.
+                (ticks / freq) * 1_000_000 + (ticks % freq) * 1_000_000 / freq
+            }
+
+            /// Convert a duration in µs to ticks, rounding down.
+            pub fn us_to_ticks(&self, us: u64) -> u64 {
+                let freq = self.tick_freq as u64;
+                (us / 1_000_000) * freq + (us % 1_000_000) * freq / 1_000_000
+            }
+
+            /// Return a deadline, in ticks, a given number of µs from now.
+            pub fn deadline(&self, us: u64) -> u64 {
+                self.now() + self.us_to_ticks(us)
+            }
+
+            /// Check if a deadline, in ticks, has passed.
+            pub fn is_expired(&self, deadline: u64) -> bool {
+                self.now() >= deadline
+            }
+
+            /// Schedule a compare interrupt at a deadline, in ticks. This replaces any
+            /// previously-set alarm. `handle_interrupt` returns `true` when it fires.
+            pub fn set_alarm(&mut self, deadline: u64) {
+                self.alarm = Some(deadline);
+                self.arm_alarm();
+            }
+
+            /// Cancel the scheduled compare interrupt, if set.
+            pub fn cancel_alarm(&mut self) {
+                self.alarm = None;
+                self.timer.tim.dier.modify(|_, w| w.cc1ie().clear_bit());
+            }
+
+            /// Handle the timer's interrupt: Count overflows, and check the alarm. Call this
+            /// from the timer's interrupt handler. Returns `true` if the alarm deadline was reached.
+            pub fn handle_interrupt(&mut self) -> bool {
+                let sr = self.timer.tim.sr.read();
+
+                if sr.uif().bit_is_set() {
+                    self.timer.clear_update_interrupt_flag();
+                    self.overflows += 1;
+                    // The deadline may be in the period that just started.
+                    self.arm_alarm();
+                }
+
+                if sr.cc1if().bit_is_set() {
+                    self.timer.tim.sr.modify(|_, w| w.cc1if().clear_bit());
+
+                    if let Some(deadline) = self.alarm {
+                        if self.now() >= deadline {
+                            self.cancel_alarm();
+                            return true;
+                        }
+                    }
+                }
+
+                false
+            }
+
+            /// Release the timer.
+            pub fn free(mut self) -> Timer<pac::$TIMX> {
+                self.timer.disable();
+                self.timer.tim.dier.modify(|_, w| {
+                    w.uie().clear_bit();
+                    w.cc1ie().clear_bit()
+                });
+                self.timer
+            }
+
+            /// Set up the channel 1 compare for the alarm, if it falls in the current
+            /// counter period. Otherwise, it's armed from the overflow interrupt.
+            fn arm_alarm(&mut self) {
+                let deadline = match self.alarm {
+                    Some(d) => d,
+                    None => return,
+                };
+
+                let now = self.now();
+                let period_start = now - now % self.period;
+
+                if deadline >= period_start + self.period {
+                    self.timer.tim.dier.modify(|_, w| w.cc1ie().clear_bit());
+                    return;
+                }
+
+                // We write CCR1 directly, since some PACs model 32-bit counters as 16 bits.
+                let compare = (deadline.max(period_start) - period_start) as u32;
+                self.timer.tim.ccr1.write(|w| unsafe { w.bits(compare) });
+                self.timer.tim.dier.modify(|_, w| w.cc1ie().set_bit());
+
+                // If the deadline passed while setting the compare value, the match won't
+                // occur; trigger the interrupt in software.
+                if self.now() >= deadline {
+                    self.timer.tim.egr.write(|w| w.cc1g().set_bit());
+                }
+            }
+        }
+    };
+}
+
+// None of the L5 timers have `pwm_features` implemented yet.
+#[cfg(not(feature = "l5"))]
 macro_rules! pwm_features {
     ($TIMX:ident, $res:ident) => {
         impl Timer<pac::$TIMX> {
@@ -503,12 +851,14 @@ macro_rules! pwm_features {
             pub fn set_duty(&mut self, channel: Channel, duty: $res) {
                 cfg_if! {
                     if #[cfg(feature = "g0")] {
-                        match channel {
-                            Channel::One => self.tim.ccr1.read().bits(),
-                            Channel::Two => self.tim.ccr2.read().bits(),
-                            Channel::Three => self.tim.ccr3.read().bits(),
-                            Channel::Four => self.tim.ccr4.read().bits(),
-                        };
+                        unsafe {
+                            match channel {
+                                Channel::One => self.tim.ccr1.write(|w| w.bits(duty)),
+                                Channel::Two => self.tim.ccr2.write(|w| w.bits(duty)),
+                                Channel::Three => self.tim.ccr3.write(|w| w.bits(duty)),
+                                Channel::Four => self.tim.ccr4.write(|w| w.bits(duty)),
+                            }
+                        }
                     } else if #[cfg(feature = "g4")] {
                         unsafe {
                             match channel {
@@ -655,6 +1005,275 @@ macro_rules! pwm_features {
                 // bit in the TIMx_EGR register."
                 self.tim.egr.write(|w| w.ug().set_bit()); // Update
             }
+
+            /// Enable or disable a DMA request. Eg, request on `Update` to load a new duty
+            /// cycle into CCRx each period.
+            pub fn set_dma_request(&mut self, request: DmaRequest, enabled: bool) {
+                match request {
+                    DmaRequest::Update => self.tim.dier.modify(|_, w| w.ude().bit(enabled)),
+                    DmaRequest::CaptureCompare(channel) => match channel {
+                        Channel::One => self.tim.dier.modify(|_, w| w.cc1de().bit(enabled)),
+                        Channel::Two => self.tim.dier.modify(|_, w| w.cc2de().bit(enabled)),
+                        Channel::Three => self.tim.dier.modify(|_, w| w.cc3de().bit(enabled)),
+                        Channel::Four => self.tim.dier.modify(|_, w| w.cc4de().bit(enabled)),
+                    },
+                }
+            }
+
+            /// Configure DMA burst mode. `base_addr` is the offset of the first register
+            /// to write, in words from `CR1` (eg `0x34 / 4 = 13` for `CCR1`), and `burst_len`
+            /// is the number of consecutive registers (1 - 18) written per DMA request.
+            /// Point the DMA channel's peripheral address at `dma_burst_addr()`.
+            /// F303 RM, section 21.4.19: "The DMAR register is used to access a burst of
+            /// registers in the TIMx_DCR register."
+            pub fn set_dma_burst(
+                &mut self,
+                base_addr: u8,
+                burst_len: u8,
+            ) -> Result<(), ValueError> {
+                if base_addr > 0b1_1111 || burst_len == 0 || burst_len > 18 {
+                    return Err(ValueError {});
+                }
+
+                self.tim.dcr.write(|w| unsafe {
+                    w.dba().bits(base_addr);
+                    w.dbl().bits(burst_len - 1)
+                });
+
+                Ok(())
+            }
+
+            /// Return the address of the `DMAR` register, for use as the DMA peripheral
+            /// address in burst mode.
+            pub fn dma_burst_addr(&self) -> u32 {
+                &self.tim.dmar as *const _ as u32
+            }
+
+            /// Return the address of a channel's `CCRx` register, for use as the DMA peripheral
+            /// address when updating a single channel's duty cycle.
+            pub fn duty_addr(&self, channel: Channel) -> u32 {
+                match channel {
+                    Channel::One => &self.tim.ccr1 as *const _ as u32,
+                    Channel::Two => &self.tim.ccr2 as *const _ as u32,
+                    Channel::Three => &self.tim.ccr3 as *const _ as u32,
+                    Channel::Four => &self.tim.ccr4 as *const _ as u32,
+                }
+            }
+
+            /// Convert a byte stream into duty cycle values, one per bit, MSB first, based on
+            /// `get_max_duty()`. The result can be written to `CCRx` by DMA on each update
+            /// event, or with `write_waveform`. Slots in `buf` after the encoded bits are set to
+            /// 0; leave enough of them to cover the protocol's reset or inter-frame gap. Returns
+            /// the number of encoded bits.
+            pub fn encode_waveform(
+                &self,
+                protocol: WaveformProtocol,
+                data: &[u8],
+                buf: &mut [$res],
+            ) -> Result<usize, ValueError> {
+                let num_bits = data.len() * 8;
+                if buf.len() < num_bits {
+                    return Err(ValueError {});
+                }
+
+                let max_duty = self.get_max_duty() as f32;
+                let (portion_0, portion_1) = protocol.duty_portions();
+                let duty_0 = (max_duty * portion_0) as $res;
+                let duty_1 = (max_duty * portion_1) as $res;
+
+                for (i, byte) in data.iter().enumerate() {
+                    for bit in 0..8 {
+                        buf[i * 8 + bit] = if byte & (0x80 >> bit) != 0 {
+                            duty_1
+                        } else {
+                            duty_0
+                        };
+                    }
+                }
+
+                for val in buf[num_bits..].iter_mut() {
+                    *val = 0;
+                }
+
+                Ok(num_bits)
+            }
+
+            /// Output a sequence of duty cycles on a channel, one per timer period, by
+            /// setting the duty cycle on each update event. This is a blocking alternative to DMA;
+            /// it's only suitable for slow bit rates, since each period must be serviced before
+            /// the next update. Enable the timer and channel output before calling.
+            pub fn write_waveform(&mut self, channel: Channel, duties: &[$res]) {
+                for duty in duties {
+                    while self.tim.sr.read().uif().bit_is_clear() {}
+                    self.clear_update_interrupt_flag();
+                    // With preload enabled, this takes effect at the next update event.
+                    self.set_duty(channel, *duty);
+                }
+            }
+
+            /// Split the timer into independent PWM channel handles. Configure the timer
+            /// (frequency, output compare mode, preload etc) before splitting; the timer
+            /// is left running if enabled. Use `PwmChannels::free` to get the timer back.
+            pub fn split(self) -> PwmChannels<pac::$TIMX> {
+                PwmChannels {
+                    c1: PwmChannel {
+                        channel: Channel::One,
+                        _tim: PhantomData,
+                    },
+                    c2: PwmChannel {
+                        channel: Channel::Two,
+                        _tim: PhantomData,
+                    },
+                    c3: PwmChannel {
+                        channel: Channel::Three,
+                        _tim: PhantomData,
+                    },
+                    c4: PwmChannel {
+                        channel: Channel::Four,
+                        _tim: PhantomData,
+                    },
+                    timer: self,
+                }
+            }
+        }
+
+        impl PwmChannel<pac::$TIMX> {
+            /// Enable output on this channel.
+            pub fn enable(&mut self) {
+                // CCER is shared between channels, so we modify it without interruption.
+                cortex_m::interrupt::free(|_| {
+                    let tim = unsafe { &(*pac::$TIMX::ptr()) };
+                    match self.channel {
+                        Channel::One => tim.ccer.modify(|_, w| w.cc1e().set_bit()),
+                        Channel::Two => tim.ccer.modify(|_, w| w.cc2e().set_bit()),
+                        Channel::Three => tim.ccer.modify(|_, w| w.cc3e().set_bit()),
+                        Channel::Four => tim.ccer.modify(|_, w| w.cc4e().set_bit()),
+                    }
+                });
+            }
+
+            /// Disable output on this channel.
+            pub fn disable(&mut self) {
+                cortex_m::interrupt::free(|_| {
+                    let tim = unsafe { &(*pac::$TIMX::ptr()) };
+                    match self.channel {
+                        Channel::One => tim.ccer.modify(|_, w| w.cc1e().clear_bit()),
+                        Channel::Two => tim.ccer.modify(|_, w| w.cc2e().clear_bit()),
+                        Channel::Three => tim.ccer.modify(|_, w| w.cc3e().clear_bit()),
+                        Channel::Four => tim.ccer.modify(|_, w| w.cc4e().clear_bit()),
+                    }
+                });
+            }
+
+            /// Set output polarity. See docs on the `Polarity` enum.
+            pub fn set_polarity(&mut self, polarity: Polarity) {
+                cortex_m::interrupt::free(|_| {
+                    let tim = unsafe { &(*pac::$TIMX::ptr()) };
+                    match self.channel {
+                        Channel::One => tim.ccer.modify(|_, w| w.cc1p().bit(polarity.bit())),
+                        Channel::Two => tim.ccer.modify(|_, w| w.cc2p().bit(polarity.bit())),
+                        Channel::Three => tim.ccer.modify(|_, w| w.cc3p().bit(polarity.bit())),
+                        Channel::Four => tim.ccer.modify(|_, w| w.cc4p().bit(polarity.bit())),
+                    }
+                });
+            }
+
+            /// Return the set duty period for this channel. Divide by `get_max_duty()`
+            /// to find the portion of the duty cycle used.
+            pub fn get_duty(&self) -> $res {
+                let tim = unsafe { &(*pac::$TIMX::ptr()) };
+                cfg_if! {
+                    if #[cfg(feature = "g0")] {
+                        match self.channel {
+                            Channel::One => tim.ccr1.read().bits(),
+                            Channel::Two => tim.ccr2.read().bits(),
+                            Channel::Three => tim.ccr3.read().bits(),
+                            Channel::Four => tim.ccr4.read().bits(),
+                        }
+                    } else if #[cfg(feature = "g4")] {
+                        match self.channel {
+                            Channel::One => tim.ccr1.read().ccr1().bits(),
+                            Channel::Two => tim.ccr2.read().ccr2().bits(),
+                            Channel::Three => tim.ccr3.read().ccr3().bits(),
+                            Channel::Four => tim.ccr4.read().ccr4().bits(),
+                        }
+                    } else {
+                        match self.channel {
+                            Channel::One => tim.ccr1.read().ccr().bits(),
+                            Channel::Two => tim.ccr2.read().ccr().bits(),
+                            Channel::Three => tim.ccr3.read().ccr().bits(),
+                            Channel::Four => tim.ccr4.read().ccr().bits(),
+                        }
+                    }
+                }
+            }
+
+            /// Set the duty cycle, as a portion of `get_max_duty()`. This is a single
+            /// write to this channel's CCR register.
+            pub fn set_duty(&mut self, duty: $res) {
+                let tim = unsafe { &(*pac::$TIMX::ptr()) };
+                cfg_if! {
+                    if #[cfg(feature = "g0")] {
+                        unsafe {
+                            match self.channel {
+                                Channel::One => tim.ccr1.write(|w| w.bits(duty)),
+                                Channel::Two => tim.ccr2.write(|w| w.bits(duty)),
+                                Channel::Three => tim.ccr3.write(|w| w.bits(duty)),
+                                Channel::Four => tim.ccr4.write(|w| w.bits(duty)),
+                            }
+                        }
+                    } else if #[cfg(feature = "g4")] {
+                        unsafe {
+                            match self.channel {
+                                Channel::One => tim.ccr1.write(|w| w.ccr1().bits(duty)),
+                                Channel::Two => tim.ccr2.write(|w| w.ccr2().bits(duty)),
+                                Channel::Three => tim.ccr3.write(|w| w.ccr3().bits(duty)),
+                                Channel::Four => tim.ccr4.write(|w| w.ccr4().bits(duty)),
+                            }
+                        }
+                    } else {
+                        match self.channel {
+                            Channel::One => tim.ccr1.write(|w| w.ccr().bits(duty)),
+                            Channel::Two => tim.ccr2.write(|w| w.ccr().bits(duty)),
+                            Channel::Three => tim.ccr3.write(|w| w.ccr().bits(duty)),
+                            Channel::Four => tim.ccr4.write(|w| w.ccr().bits(duty)),
+                        }
+                    }
+                }
+            }
+
+            /// Return the integer associated with the maximum duty period.
+            pub fn get_max_duty(&self) -> $res {
+                let tim = unsafe { &(*pac::$TIMX::ptr()) };
+                #[cfg(feature = "g0")]
+                return tim.arr.read().bits();
+                #[cfg(not(feature = "g0"))]
+                return tim.arr.read().arr().bits();
+            }
+        }
+
+        impl embedded_hal::PwmPin for PwmChannel<pac::$TIMX> {
+            type Duty = $res;
+
+            fn disable(&mut self) {
+                PwmChannel::<pac::$TIMX>::disable(self);
+            }
+
+            fn enable(&mut self) {
+                PwmChannel::<pac::$TIMX>::enable(self);
+            }
+
+            fn get_duty(&self) -> Self::Duty {
+                PwmChannel::<pac::$TIMX>::get_duty(self)
+            }
+
+            fn get_max_duty(&self) -> Self::Duty {
+                PwmChannel::<pac::$TIMX>::get_max_duty(self)
+            }
+
+            fn set_duty(&mut self, duty: Self::Duty) {
+                PwmChannel::<pac::$TIMX>::set_duty(self, duty);
+            }
         }
     };
 }
@@ -664,6 +1283,8 @@ macro_rules! pwm_features {
 
 #[cfg(not(any(feature = "f373")))]
 hal!(TIM1, tim1, 2);
+#[cfg(not(any(feature = "f373")))]
+monotonic!(TIM1);
 #[cfg(not(any(
     feature = "f373",
     feature = "f4",
@@ -682,6 +1303,7 @@ cfg_if! {
         feature = "g070",
     )))] {
         hal!(TIM2, tim2, 1);
+        monotonic!(TIM2);
     }
 }
 
@@ -694,8 +1316,11 @@ pwm_features!(TIM2, u32);
 
 #[cfg(not(any(feature = "f301", feature = "l4x1", feature = "l4x3", feature = "f410",)))]
 hal!(TIM3, tim3, 1);
+#[cfg(not(any(feature = "f301", feature = "l4x1", feature = "l4x3", feature = "f410",)))]
+monotonic!(TIM3);
 
 #[cfg(not(any(
+    feature = "f301",
     feature = "l4x1",
     feature = "l4x3",
     feature = "l5",
@@ -719,6 +1344,7 @@ cfg_if! {
         feature = "g0",
     )))] {
         hal!(TIM4, tim4, 1);
+        monotonic!(TIM4);
     }
 }
 
@@ -747,6 +1373,7 @@ cfg_if! {
        all(feature = "f4", not(feature = "f410")),
    ))] {
         hal!(TIM5, tim5, 1);
+        monotonic!(TIM5);
    }
 }
 
@@ -796,6 +1423,14 @@ hal!(TIM7, tim7, 1);
     feature = "g4"
 ))]
 hal!(TIM8, tim8, 2);
+#[cfg(any(
+    feature = "f303",
+    feature = "l4x5",
+    feature = "l4x6",
+    feature = "l562",
+    feature = "g4"
+))]
+monotonic!(TIM8);
 
 // Todo: the L5 PAC has an address error on TIM15 - remove it until solved.
 #[cfg(not(any(
@@ -807,9 +1442,20 @@ hal!(TIM8, tim8, 2);
     feature = "g030"
 )))]
 hal!(TIM15, tim15, 2);
+#[cfg(not(any(
+    feature = "l5",
+    feature = "f4",
+    feature = "g031",
+    feature = "g031",
+    feature = "g041",
+    feature = "g030"
+)))]
+monotonic!(TIM15);
 
 #[cfg(not(feature = "f4"))]
 hal!(TIM16, tim16, 2);
+#[cfg(not(feature = "f4"))]
+monotonic!(TIM16);
 
 cfg_if! {
     if #[cfg(not(any(
@@ -819,6 +1465,7 @@ cfg_if! {
         feature = "f4",
     )))] {
         hal!(TIM17, tim17, 2);
+        monotonic!(TIM17);
     }
 }
 
@@ -832,8 +1479,14 @@ cfg_if! {
         hal!(TIM13, tim13, 1);
         hal!(TIM14, tim14, 1);
         hal!(TIM19, tim19, 2);
+        monotonic!(TIM12);
+        monotonic!(TIM13);
+        monotonic!(TIM14);
+        monotonic!(TIM19);
     }
 }
 
 #[cfg(any(feature = "f303"))]
 hal!(TIM20, tim20, 2);
+#[cfg(any(feature = "f303"))]
+monotonic!(TIM20);
@@ -36,7 +36,11 @@ pub trait ClockCfg {
     /// APB2 peripheral common speed, in Hz.
     fn apb2_timer(&self) -> u32;
 
-    // Todo: Optional APB3, APB4, AHB2, AHB4, HCLK3. These are for H7.
+    #[cfg(feature = "h7")]
+    /// APB3 peripheral common speed, in Hz. (D1 domain)
+    fn apb3(&self) -> u32;
+
+    // Todo: Optional APB4, AHB2, AHB4, HCLK3. These are for H7.
 
     /// Validate that the clocks speeds are all within the acceptable range
     /// for the MCU
@@ -10,6 +10,7 @@ use core::{ops::Deref, ptr};
 
 use crate::{
     pac::{self, RCC},
+    pins::{RxPin, TxPin},
     rcc_en_reset,
     traits::ClockCfg,
 };
@@ -234,6 +235,25 @@ where
         result
     }
 
+    /// Like `new`, but takes the TX and RX pins, so a pin or alternate function that
+    /// can't be used with this USART fails to compile.
+    pub fn new_with_pins<TX, RX, C>(
+        regs: U,
+        device: UsartDevice,
+        _pins: (TX, RX),
+        baud: u32,
+        config: UsartConfig,
+        clock_cfg: &C,
+        rcc: &mut RCC,
+    ) -> Self
+    where
+        TX: TxPin<U>,
+        RX: RxPin<U>,
+        C: ClockCfg,
+    {
+        Self::new(regs, device, baud, config, clock_cfg, rcc)
+    }
+
     /// Set the BAUD rate. Called during init, and can be called later to change BAUD
     /// during program execution.
     pub fn set_baud<C: ClockCfg>(&mut self, baud: u32, clock_cfg: &C) {
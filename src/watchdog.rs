@@ -0,0 +1,326 @@
+//! Independent (IWDG) and window (WWDG) watchdogs. The IWDG is clocked by LSI, and runs
+//! independently of the main clock; once started, it can't be stopped until reset. The WWDG is
+//! clocked from APB, and can generate an early wakeup interrupt before it resets the MCU.
+//!
+//! See L4 RM, sections 32 (IWDG) and 33 (WWDG).
+
+use embedded_hal::watchdog::{Watchdog, WatchdogEnable};
+
+use crate::{pac::RCC, traits::ClockCfg};
+
+use cfg_if::cfg_if;
+
+cfg_if! {
+    if #[cfg(any(feature = "h747cm4", feature = "h747cm7"))] {
+        use crate::pac::{IWDG1 as IWDG, WWDG1 as WWDG};
+    } else {
+        use crate::pac::{IWDG, WWDG};
+    }
+}
+
+cfg_if! {
+    if #[cfg(feature = "g0")] {
+        use crate::pac::DBG as DBGMCU;
+    } else {
+        use crate::pac::DBGMCU;
+    }
+}
+
+// Nominal LSI frequency, in Hz. Note that LSI isn't precise; see the datasheet for its
+// tolerance, and leave margin in timeouts accordingly.
+#[cfg(feature = "f3")]
+const LSI_FREQ: u32 = 40_000;
+#[cfg(not(feature = "f3"))]
+const LSI_FREQ: u32 = 32_000;
+
+// IWDG key register values. L4 RM, section 32.4.1.
+const KEY_RELOAD: u32 = 0xAAAA;
+const KEY_ACCESS: u32 = 0x5555;
+const KEY_START: u32 = 0xCCCC;
+
+// Largest 3-bit WDGTB prescaler value. (Divides by 2^WDGTB)
+cfg_if! {
+    if #[cfg(any(feature = "g0", feature = "g4"))] {
+        const WWDG_MAX_PSC: u8 = 7;
+    } else {
+        const WWDG_MAX_PSC: u8 = 3;
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// Used for when attempting to set a timeout or window that is out of range.
+pub struct ValueError {}
+
+/// Independent watchdog
+pub struct Iwdg {
+    regs: IWDG,
+}
+
+impl Iwdg {
+    /// Create a new IWDG instance. The watchdog isn't started until `start` is called.
+    pub fn new(regs: IWDG) -> Self {
+        Self { regs }
+    }
+
+    /// Start the watchdog, with a timeout in milliseconds. Call `feed` before
+    /// this elapses to prevent a reset. The max timeout is about 32s with a 32kHz LSI.
+    pub fn start(&mut self, timeout_ms: u32) -> Result<(), ValueError> {
+        let (psc, rlr) = iwdg_vals(timeout_ms)?;
+
+        // 1. Enable the IWDG by writing 0x0000 CCCC in the IWDG key register (IWDG_KR).
+        self.regs.kr.write(|w| unsafe { w.bits(KEY_START) });
+        // 2. Enable register access by writing 0x0000 5555 in the IWDG key register (IWDG_KR).
+        self.regs.kr.write(|w| unsafe { w.bits(KEY_ACCESS) });
+        // 3. Write the prescaler by programming the IWDG prescaler register (IWDG_PR) from 0 to 7.
+        self.regs.pr.write(|w| unsafe { w.bits(psc as u32) });
+        // 4. Write the IWDG reload register (IWDG_RLR).
+        self.regs.rlr.write(|w| unsafe { w.bits(rlr as u32) });
+        // 5. Wait for the registers to be updated (IWDG_SR = 0x0000 0000).
+        while self.regs.sr.read().bits() != 0 {}
+        // 6. Refresh the counter value with IWDG_RLR (IWDG_KR = 0x0000 AAAA)
+        self.feed();
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "f4"))]
+    /// Start the watchdog in window mode. `feed` only prevents a reset if called within
+    /// `window_ms` of the timeout; feeding earlier causes a reset. The window must be at least
+    /// one counter tick, ie `4 * 2^PR / LSI` (0.1 - 0.125ms at the shortest prescaler), or
+    /// this returns an error, since no feed would be allowed.
+    pub fn start_windowed(&mut self, timeout_ms: u32, window_ms: u32) -> Result<(), ValueError> {
+        let (psc, rlr) = iwdg_vals(timeout_ms)?;
+        // The window is expressed as a counter value: Refreshes are allowed when the
+        // down-counter is below it.
+        let winr = (window_ms as u64 * LSI_FREQ as u64 / (1_000 * (4 << psc) as u64)) as u32;
+        if winr == 0 || winr > rlr as u32 {
+            return Err(ValueError {});
+        }
+
+        self.regs.kr.write(|w| unsafe { w.bits(KEY_START) });
+        self.regs.kr.write(|w| unsafe { w.bits(KEY_ACCESS) });
+        self.regs.pr.write(|w| unsafe { w.bits(psc as u32) });
+        self.regs.rlr.write(|w| unsafe { w.bits(rlr as u32) });
+        while self.regs.sr.read().bits() != 0 {}
+        // "Writing the window value causes a refresh of the down-counter to the RLR value."
+        self.regs.winr.write(|w| unsafe { w.bits(winr) });
+
+        Ok(())
+    }
+
+    /// Reload the watchdog counter, preventing a reset.
+    pub fn feed(&mut self) {
+        self.regs.kr.write(|w| unsafe { w.bits(KEY_RELOAD) });
+    }
+
+    /// Stop the watchdog counter while the core is halted by a debugger.
+    pub fn freeze_on_debug(&mut self, dbgmcu: &mut DBGMCU) {
+        cfg_if! {
+            if #[cfg(any(feature = "f3", feature = "f4"))] {
+                dbgmcu.apb1_fz.modify(|_, w| w.dbg_iwdg_stop().set_bit());
+            } else if #[cfg(any(feature = "l4x5", feature = "l4x6"))] {
+                dbgmcu.apb1_fzr1.modify(|_, w| w.dbg_iwdg_stop().set_bit());
+            } else if #[cfg(feature = "l4")] {
+                dbgmcu.apb1fzr1.modify(|_, w| w.dbg_iwdg_stop().set_bit());
+            } else if #[cfg(feature = "l5")] {
+                dbgmcu.apb1lfzr.modify(|_, w| w.dbg_iwdg_stop().set_bit());
+            } else if #[cfg(feature = "g0")] {
+                dbgmcu.apb_fz1.modify(|_, w| w.dbg_iwdg_stop().set_bit());
+            } else if #[cfg(feature = "g4")] {
+                dbgmcu.apb1l_fz.modify(|_, w| w.dbg_iwdg_stop().set_bit());
+            } else { // H7
+                dbgmcu.apb4fz1.modify(|_, w| w.iwdg1().set_bit());
+            }
+        }
+    }
+}
+
+/// Calculate the IWDG prescaler and reload values for a timeout. Uses the smallest
+/// prescaler that fits, for the best resolution.
+fn iwdg_vals(timeout_ms: u32) -> Result<(u8, u16), ValueError> {
+    // t = (4 * 2^PR) * (RLR + 1) / LSI. PR ranges from 0 to 6, RLR from 0 to 0xfff.
+    for psc in 0..=6 {
+        let ticks = timeout_ms as u64 * LSI_FREQ as u64 / (1_000 * (4 << psc) as u64);
+        if ticks <= 0x1000 {
+            if ticks == 0 {
+                return Err(ValueError {});
+            }
+            return Ok((psc, (ticks - 1) as u16));
+        }
+    }
+    Err(ValueError {})
+}
+
+impl Watchdog for Iwdg {
+    fn feed(&mut self) {
+        Iwdg::feed(self);
+    }
+}
+
+impl WatchdogEnable for Iwdg {
+    type Time = u32; // ms
+
+    fn start<T>(&mut self, period: T)
+    where
+        T: Into<u32>,
+    {
+        // The trait can't return an error, and silently leaving the watchdog stopped is worse
+        // than panicking.
+        Iwdg::start(self, period.into()).expect("IWDG timeout is out of range");
+    }
+}
+
+/// Window watchdog
+pub struct Wwdg {
+    regs: WWDG,
+    pclk: u32,  // APB clock speed, in Hz.
+    reload: u8, // Value written to the T bits on each refresh.
+}
+
+impl Wwdg {
+    /// Create a new WWDG instance, and enable its peripheral clock. The watchdog isn't started
+    /// until `start` is called.
+    pub fn new<C: ClockCfg>(regs: WWDG, clocks: &C, rcc: &mut RCC) -> Self {
+        cfg_if! {
+            if #[cfg(any(feature = "f3", feature = "f4"))] {
+                rcc.apb1enr.modify(|_, w| w.wwdgen().set_bit());
+            } else if #[cfg(any(feature = "l4", feature = "l5", feature = "g4"))] {
+                rcc.apb1enr1.modify(|_, w| w.wwdgen().set_bit());
+            } else if #[cfg(feature = "g0")] {
+                rcc.apbenr1.modify(|_, w| w.wwdgen().set_bit());
+            } else { // H7
+                rcc.apb3enr.modify(|_, w| w.wwdg1en().set_bit());
+            }
+        }
+
+        // The WWDG is clocked from APB1; on H7, from APB3.
+        #[cfg(feature = "h7")]
+        let pclk = clocks.apb3();
+        #[cfg(not(feature = "h7"))]
+        let pclk = clocks.apb1();
+
+        Self {
+            regs,
+            pclk,
+            reload: 0x7f,
+        }
+    }
+
+    /// Start the watchdog, with a timeout in milliseconds. Call `feed` before
+    /// this elapses to prevent a reset.
+    pub fn start(&mut self, timeout_ms: u32) -> Result<(), ValueError> {
+        let (psc, counts) = self.wwdg_vals(timeout_ms)?;
+        self.reload = 0x3f + counts;
+
+        // The window is left at its max value, so refreshing is allowed at any time.
+        self.regs
+            .cfr
+            .modify(|_, w| unsafe { w.wdgtb().bits(psc).w().bits(0x7f) });
+        self.enable();
+
+        Ok(())
+    }
+
+    /// Start the watchdog in window mode. `feed` only prevents a reset if called within
+    /// `window_ms` of the timeout; feeding earlier causes a reset. The window must be at least
+    /// one counter tick, ie `4_096 * 2^WDGTB / PCLK`, or this returns an error, since no feed
+    /// would be allowed.
+    pub fn start_windowed(&mut self, timeout_ms: u32, window_ms: u32) -> Result<(), ValueError> {
+        let (psc, counts) = self.wwdg_vals(timeout_ms)?;
+        let window =
+            (window_ms as u64 * self.pclk as u64 / (1_000 * 4_096 * (1 << psc) as u64)) as u8;
+        // With a window of 0, W is 0x3f, and the counter is never below it while running.
+        if window == 0 || window > counts {
+            return Err(ValueError {});
+        }
+        self.reload = 0x3f + counts;
+
+        // Refreshes are allowed when the down-counter is below W, and above 0x3f.
+        self.regs
+            .cfr
+            .modify(|_, w| unsafe { w.wdgtb().bits(psc).w().bits(0x3f + window) });
+        self.enable();
+
+        Ok(())
+    }
+
+    /// Set the T bits, and enable the watchdog. Once enabled, it can only be disabled by a reset.
+    fn enable(&mut self) {
+        // Write WDGA and T in a single write, so we don't trigger a reset with a stale counter.
+        self.regs
+            .cr
+            .write(|w| unsafe { w.bits(0x80 | self.reload as u32) });
+    }
+
+    /// Calculate the prescaler, and the number of counter ticks for a timeout.
+    fn wwdg_vals(&self, timeout_ms: u32) -> Result<(u8, u8), ValueError> {
+        // t = 4096 * 2^WDGTB * (T[5:0] + 1) / PCLK. T[5:0] ranges from 0 to 0x3f.
+        for psc in 0..=WWDG_MAX_PSC {
+            let counts = timeout_ms as u64 * self.pclk as u64 / (1_000 * 4_096 * (1 << psc) as u64);
+            if counts <= 0x40 {
+                if counts == 0 {
+                    return Err(ValueError {});
+                }
+                return Ok((psc, counts as u8));
+            }
+        }
+        Err(ValueError {})
+    }
+
+    /// Reload the watchdog counter, preventing a reset.
+    pub fn feed(&mut self) {
+        self.enable();
+    }
+
+    /// Enable the early wakeup interrupt. This fires when the counter reaches 0x40, one
+    /// counter tick before the reset. It can be used to feed the watchdog, or to save state
+    /// before the reset.
+    pub fn enable_interrupt(&mut self) {
+        self.regs.cfr.modify(|_, w| w.ewi().set_bit());
+    }
+
+    /// Clear the early wakeup interrupt flag. Call this in the WWDG interrupt handler.
+    pub fn clear_interrupt(&mut self) {
+        self.regs.sr.write(|w| unsafe { w.bits(0) });
+    }
+
+    /// Stop the watchdog counter while the core is halted by a debugger.
+    pub fn freeze_on_debug(&mut self, dbgmcu: &mut DBGMCU) {
+        cfg_if! {
+            if #[cfg(any(feature = "f3", feature = "f4"))] {
+                dbgmcu.apb1_fz.modify(|_, w| w.dbg_wwdg_stop().set_bit());
+            } else if #[cfg(any(feature = "l4x5", feature = "l4x6"))] {
+                dbgmcu.apb1_fzr1.modify(|_, w| w.dbg_wwdg_stop().set_bit());
+            } else if #[cfg(feature = "l4")] {
+                dbgmcu.apb1fzr1.modify(|_, w| w.dbg_wwdg_stop().set_bit());
+            } else if #[cfg(feature = "l5")] {
+                dbgmcu.apb1lfzr.modify(|_, w| w.dbg_wwdg_stop().set_bit());
+            } else if #[cfg(feature = "g0")] {
+                dbgmcu.apb_fz1.modify(|_, w| w.dbg_wwdg_stop().set_bit());
+            } else if #[cfg(feature = "g4")] {
+                dbgmcu.apb1l_fz.modify(|_, w| w.dbg_wwdg_stop().set_bit());
+            } else { // H7
+                dbgmcu.apb3fz1.modify(|_, w| w.wwdg1().set_bit());
+            }
+        }
+    }
+}
+
+impl Watchdog for Wwdg {
+    fn feed(&mut self) {
+        Wwdg::feed(self);
+    }
+}
+
+impl WatchdogEnable for Wwdg {
+    type Time = u32; // ms
+
+    fn start<T>(&mut self, period: T)
+    where
+        T: Into<u32>,
+    {
+        // The trait can't return an error, and silently leaving the watchdog stopped is worse
+        // than panicking.
+        Wwdg::start(self, period.into()).expect("WWDG timeout is out of range for the APB clock");
+    }
+}